@@ -1,4 +1,5 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
 
 #[derive(ValueEnum, Clone, Debug)]
 pub enum RunOutputType {
@@ -7,6 +8,40 @@ pub enum RunOutputType {
     Json,
 }
 
+/// Variables seeded into a script before it runs
+///
+/// Precedence, from highest to lowest: `--var`, assignments in the script,
+/// then the selected `env` block.
+#[derive(Args, Clone, Debug, Default)]
+pub struct EnvArgs {
+    /// Environment declared with `env <name> { ... }` in the script or in `nero.env.ns`
+    #[arg(short, long)]
+    pub env: Option<String>,
+
+    /// Override a variable, e.g. `--var url=http://localhost:3000` (repeatable)
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub vars: Vec<(String, String)>,
+}
+
+/// How the requests of a script are sent
+#[derive(Args, Clone, Debug)]
+pub struct RunArgs {
+    /// Maximum number of requests in flight; dependent requests still wait for each other
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    pub parallel: u16,
+
+    /// Load cookies from this Netscape or JSON file before the run and save them back after
+    #[arg(long, value_name = "FILE")]
+    pub cookie_jar: Option<PathBuf>,
+}
+
+fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((k, v)) if !k.is_empty() => Ok((k.to_string(), v.to_string())),
+        _ => Err(format!("expected KEY=VALUE, found `{}`", s)),
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(
     name = "nero",
@@ -32,18 +67,65 @@ pub enum Commands {
         /// Type output print
         #[arg(short, long, value_enum, default_value_t = RunOutputType::Json)]
         output: RunOutputType,
+
+        #[command(flatten)]
+        run: RunArgs,
+
+        #[command(flatten)]
+        env: EnvArgs,
+    },
+
+    /// Execute request from file and check its assertions
+    Test {
+        /// Path to request file
+        file: String,
+
+        #[command(flatten)]
+        run: RunArgs,
+
+        #[command(flatten)]
+        env: EnvArgs,
+    },
+
+    /// Validate request file without sending any request
+    Check {
+        /// Path to request file
+        file: String,
+
+        #[command(flatten)]
+        env: EnvArgs,
     },
 
-    /// Execute request from line (WIP)
+    /// Execute a single request from the command line
     Fetch {
-        /// HTTP method
-        #[arg(short, long, default_value = "GET")]
+        /// HTTP method, `-X` is accepted too as in curl
+        #[arg(short, short_alias = 'X', long, default_value = "GET")]
         method: String,
 
         /// Request timeout (seconds)
         #[arg(short, long)]
         timeout: Option<u64>,
 
+        /// Request header, e.g. `-H 'Accept: application/json'` (repeatable)
+        #[arg(short = 'H', long = "header", value_name = "KEY: VALUE")]
+        headers: Vec<String>,
+
+        /// Query parameter, e.g. `-q page=2` (repeatable)
+        #[arg(short, long, value_name = "KEY=VALUE", value_parser = parse_key_value)]
+        query: Vec<(String, String)>,
+
+        /// Raw request body, or `@file` to read it from a file
+        #[arg(short, long)]
+        data: Option<String>,
+
+        /// JSON request body, or `@file` to read it from a file
+        #[arg(long, conflicts_with = "data")]
+        json: Option<String>,
+
+        /// Type output print
+        #[arg(short, long, value_enum, default_value_t = RunOutputType::Json)]
+        output: RunOutputType,
+
         /// Target URL (positional argument)
         url: String,
     },
@@ -0,0 +1,109 @@
+use colored::Colorize;
+use nero_core::span::Span;
+
+/// A single error pointing at a location in a script
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl ToString, span: Span) -> Self {
+        Self {
+            message: message.to_string(),
+            span,
+        }
+    }
+
+    /// Render the diagnostic rustc-style, with the source line and a caret
+    /// under the offending span.
+    ///
+    /// ```text
+    /// error: Expected: Colon, found: StringLiteral("x")
+    ///  --> dev/sample/simple_get.ns:3:9
+    ///   |
+    /// 3 |     "a" "x"
+    ///   |         ^^^
+    /// ```
+    pub fn render(&self, file: &str, source: &str) -> String {
+        let line_no = self.span.line.to_string();
+        let pad = " ".repeat(line_no.len());
+        let line = source
+            .lines()
+            .nth(self.span.line.saturating_sub(1))
+            .unwrap_or("");
+
+        let column = self.span.column.saturating_sub(1);
+        let line_len = line.chars().count();
+        let width = source
+            .get(self.span.start..self.span.end)
+            .map(|s| s.lines().next().unwrap_or("").chars().count())
+            .unwrap_or(0)
+            .min(line_len.saturating_sub(column))
+            .max(1);
+
+        let gutter = format!("{} |", pad).blue().bold();
+        format!(
+            "{}: {}\n{}{} {}:{}:{}\n{}\n{} {}\n{} {}{}",
+            "error".red().bold(),
+            self.message.bold(),
+            pad,
+            "-->".blue().bold(),
+            file,
+            self.span.line,
+            self.span.column,
+            gutter,
+            format!("{} |", line_no).blue().bold(),
+            line,
+            gutter,
+            " ".repeat(column),
+            "^".repeat(width).red().bold(),
+        )
+    }
+}
+
+/// Every diagnostic produced while loading a script
+#[derive(Debug)]
+pub struct ScriptError {
+    /// `(file, source)` of every file the diagnostics point into
+    pub sources: Vec<(String, String)>,
+    /// Each diagnostic along with the index of its file in `sources`
+    pub diagnostics: Vec<(usize, Diagnostic)>,
+}
+
+impl ScriptError {
+    pub fn new(file: &str, source: &str, diagnostics: Vec<Diagnostic>) -> Self {
+        Self {
+            sources: vec![(file.to_string(), source.to_string())],
+            diagnostics: diagnostics.into_iter().map(|d| (0, d)).collect(),
+        }
+    }
+
+    /// Diagnostics spread over several files, e.g. a script and its imports
+    pub fn with_sources(
+        sources: Vec<(String, String)>,
+        diagnostics: Vec<(usize, Diagnostic)>,
+    ) -> Self {
+        Self {
+            sources,
+            diagnostics,
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rendered: Vec<String> = self
+            .diagnostics
+            .iter()
+            .map(|(i, d)| {
+                let (file, source) = &self.sources[*i];
+                d.render(file, source)
+            })
+            .collect();
+        write!(f, "{}", rendered.join("\n\n"))
+    }
+}
@@ -0,0 +1,30 @@
+/// Parse the contents of a `.env` file into `(key, value)` pairs.
+///
+/// Supports `KEY=value`, an optional `export ` prefix, `#` comments and
+/// values wrapped in single or double quotes. Lines without `=` are ignored.
+pub fn parse(source: &str) -> Vec<(String, String)> {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() {
+                return None;
+            }
+
+            let value = value.trim();
+            let value = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) if value.len() > 1 && value.ends_with(quote) => {
+                    &value[1..value.len() - 1]
+                }
+                // trailing comment after an unquoted value
+                _ => value.split(" #").next().unwrap_or("").trim_end(),
+            };
+
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
@@ -1,2 +1,4 @@
 pub mod args;
+pub mod diagnostic;
+pub mod dotenv;
 pub mod output;
@@ -1,9 +1,18 @@
+use std::{fmt::Write, io::IsTerminal};
+
 use colored::Colorize;
 use tabled::{
     Table, Tabled,
     settings::{Alignment, Color, Modify, object::Rows, style::Style},
 };
 
+use crate::{cli::args::RunOutputType, cmds::run::RunCmd};
+
+/// Maximum number of characters of the body shown by the table output
+const BODY_PREVIEW_CHARS: usize = 200;
+/// Maximum number of lines of the body shown by the table output
+const BODY_PREVIEW_LINES: usize = 8;
+
 #[derive(Tabled)]
 pub struct PrintSummary {
     pub label: String,
@@ -16,10 +25,10 @@ pub struct PrintSummary {
 #[derive(Tabled)]
 pub struct PrintTable {
     pub label: String,
-    pub method: String,
+    pub request: String,
     pub status: String,
-    pub time: String,
-    pub size: String,
+    pub headers: String,
+    pub body: String,
 }
 
 pub struct OutputPrint {
@@ -27,16 +36,169 @@ pub struct OutputPrint {
 }
 
 impl OutputPrint {
+    /// Print results of `file` in the format selected with `--output`
+    pub fn print(output: &RunOutputType, file: &str, data: &[RunCmd]) {
+        match output {
+            RunOutputType::Json => Self::json(file, data),
+            RunOutputType::Summary => Self::summary(file, data),
+            RunOutputType::Table => Self::table(file, data),
+        }
+    }
+
     // pub fn new(data: &Vec<crate::cmds::run::RunCmd>) -> Self {
     //     Self { data }
     // }
-    pub fn json(data: &Vec<crate::cmds::run::RunCmd>) {}
-    pub fn table(data: &Vec<crate::cmds::run::RunCmd>) {}
-    pub fn summary(data: &Vec<crate::cmds::run::RunCmd>) {
-        println!("File: {}", data[0].file.green());
+    /// Print results as a single JSON document, meant to be piped into `jq`.
+    ///
+    /// ```json
+    /// {
+    ///   "file": "dev/sample/simple_get.ns",
+    ///   "timestamp": "2025-01-01T00:00:00+00:00",
+    ///   "requests": [
+    ///     {
+    ///       "label": "simple_get",
+    ///       "doc": "Fetch the home page",
+    ///       "method": "GET",
+    ///       "url": "http://127.0.0.1:3000/",
+    ///       "status": 200,
+    ///       "headers": { "content-type": "application/json" },
+    ///       "body": { "msg": "Ok" },
+    ///       "duration_ms": 1,
+    ///       "size": 12,
+    ///       "assertions": [{ "expr": "status == 200", "passed": true, "message": null }],
+    ///       "error": null,
+    ///       "attempts": [{ "status": 200, "error": null, "duration_ms": 1 }]
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// `doc` is the request's `///` doc comment, or `null` when it has none.
+    /// `error` says why a request got no response, and is `null` otherwise.
+    /// `body` is the parsed JSON when the response is valid JSON and a string
+    /// otherwise. Header names are lowercase, a repeated header such as
+    /// `set-cookie` becomes an array of its values. `attempts` lists every
+    /// try of a retried request, the last one being the response shown.
+    pub fn json(file: &str, data: &[RunCmd]) {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&Self::to_json(file, data)).unwrap_or_default()
+        );
+    }
+
+    /// The document printed by [`OutputPrint::json`]
+    pub fn to_json(file: &str, data: &[RunCmd]) -> serde_json::Value {
+        let requests: Vec<serde_json::Value> = data
+            .iter()
+            .map(|f| {
+                let mut headers = serde_json::Map::new();
+                for (k, v) in &f.headers {
+                    match headers.get_mut(k) {
+                        Some(serde_json::Value::Array(values)) => values.push(v.clone().into()),
+                        Some(prev) => *prev = serde_json::json!([prev.take(), v]),
+                        None => {
+                            headers.insert(k.clone(), v.clone().into());
+                        }
+                    }
+                }
+
+                let body = serde_json::from_str::<serde_json::Value>(&f.body)
+                    .unwrap_or_else(|_| f.body.clone().into());
+
+                let assertions: Vec<serde_json::Value> = f
+                    .assertions
+                    .iter()
+                    .map(|a| {
+                        serde_json::json!({
+                            "expr": a.expr,
+                            "passed": a.passed,
+                            "message": a.message,
+                        })
+                    })
+                    .collect();
+
+                serde_json::json!({
+                    "label": f.label,
+                    "doc": f.doc,
+                    "method": f.method,
+                    "url": f.url,
+                    "status": f.status,
+                    "headers": headers,
+                    "body": body,
+                    "duration_ms": f.duration_ms,
+                    "size": f.size,
+                    "assertions": assertions,
+                    "error": f.error,
+                    "attempts": f
+                        .attempts
+                        .iter()
+                        .map(|a| {
+                            serde_json::json!({
+                                "status": a.status,
+                                "error": a.error,
+                                "duration_ms": a.duration_ms,
+                            })
+                        })
+                        .collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "file": file,
+            "timestamp": timestamp(data),
+            "requests": requests,
+        })
+    }
+
+    /// Print one row per request with its response headers and a preview of the body
+    pub fn table(file: &str, data: &[RunCmd]) {
+        Self::print_header(file, data);
+        println!("{}", Self::to_table(data));
+    }
+
+    /// The table printed by [`OutputPrint::table`]
+    pub fn to_table(data: &[RunCmd]) -> String {
+        let data_table: Vec<PrintTable> = data
+            .iter()
+            .map(|f| PrintTable {
+                label: format_label(f),
+                request: format!("{} {}", f.method.to_uppercase(), f.url),
+                status: format!(
+                    "{}\n{}\n{}",
+                    format_status(f),
+                    format_time(f.duration_ms),
+                    format_size(f.size)
+                ),
+                headers: f
+                    .headers
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k, v))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                body: body_preview(&f.body),
+            })
+            .collect();
 
-        println!("Time: {}\n", data[0].date.green());
+        style_table(Table::new(data_table)).to_string()
+    }
+
+    fn print_header(file: &str, data: &[RunCmd]) {
+        if !colors_enabled() {
+            colored::control::set_override(false);
+        }
+        println!("File: {}", file.green());
+
+        println!("Time: {}\n", timestamp(data).green());
+    }
 
+    pub fn summary(file: &str, data: &[RunCmd]) {
+        Self::print_header(file, data);
+        println!("{}", Self::to_summary(data));
+    }
+
+    /// The table printed by [`OutputPrint::summary`]
+    pub fn to_summary(data: &[RunCmd]) -> String {
         // [1/2] Running: simple_get... OK
         // [2/2] Running: test_get_user... OK
 
@@ -48,36 +210,144 @@ impl OutputPrint {
         // │ test_get_user   │ GET    │ 200    │ 0 ms   │ 8 B  │
         // └─────────────────┴────────┴────────┴────────┴──────┘
         let data_table: Vec<PrintSummary> = data
-            .into_iter()
-            .map(|f| {
-                let time_str = format!("{} ms", f.duration_ms);
-                let size_str = if f.size >= 1024 {
-                    format!("{:.2} KB", f.size as f64 / 1024.0)
-                } else {
-                    format!("{} B", f.size)
-                };
-
-                PrintSummary {
-                    label: f.label.clone(),
-                    method: f.method.to_uppercase(),
-                    status: f.status.to_string(),
-                    time: time_str,
-                    size: size_str,
-                }
+            .iter()
+            .map(|f| PrintSummary {
+                label: format_label(f),
+                method: f.method.to_uppercase(),
+                status: format_status(f),
+                time: format_time(f.duration_ms),
+                size: format_size(f.size),
             })
             .collect();
 
-        let mut table = Table::new(data_table);
+        style_table(Table::new(data_table)).to_string()
+    }
+
+    /// Print pass/fail for every request and assertion
+    ///
+    /// # Return
+    /// `true` if every assertion passed
+    pub fn test(file: &str, data: &[RunCmd]) -> bool {
+        Self::print_header(file, data);
+        let (report, ok) = Self::to_test_report(data);
+        print!("{}", report);
+        ok
+    }
+
+    /// The report printed by [`OutputPrint::test`], and `true` if every
+    /// assertion passed
+    pub fn to_test_report(data: &[RunCmd]) -> (String, bool) {
+        let mut report = String::new();
+        let mut passed = 0;
+        let mut failed = 0;
+
+        for run in data {
+            let ok = run.error.is_none() && run.assertions.iter().all(|a| a.passed);
+            if ok {
+                passed += 1;
+                let _ = writeln!(report, "{} {}", "PASS".green().bold(), run.label);
+            } else {
+                failed += 1;
+                let _ = writeln!(report, "{} {}", "FAIL".red().bold(), run.label);
+            }
+            for line in run.doc.iter().flat_map(|doc| doc.lines()) {
+                let _ = writeln!(report, "  {}", line.dimmed());
+            }
+
+            if let Some(error) = &run.error {
+                let _ = writeln!(report, "  {} {}", "✗".red(), error);
+            }
+            for assertion in &run.assertions {
+                let _ = match (&assertion.message, assertion.passed) {
+                    (_, true) => writeln!(report, "  {} {}", "✓".green(), assertion.expr),
+                    (Some(message), false) => {
+                        writeln!(report, "  {} {} ({})", "✗".red(), assertion.expr, message)
+                    }
+                    (None, false) => writeln!(report, "  {} {}", "✗".red(), assertion.expr),
+                };
+            }
+        }
+
+        let _ = writeln!(
+            report,
+            "\n{} passed, {} failed",
+            passed.to_string().green(),
+            failed.to_string().red()
+        );
+
+        (report, failed == 0)
+    }
+}
+
+/// Colors are only used when printing to a terminal and `NO_COLOR` is not set
+fn colors_enabled() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
+
+/// Time of the run, taken from the first request
+fn timestamp(data: &[RunCmd]) -> String {
+    data.first()
+        .map(|f| f.date.clone())
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339())
+}
+
+fn style_table(mut table: Table) -> Table {
+    table
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()));
+    if colors_enabled() {
+        table.with(Modify::new(Rows::first()).with(Color::FG_CYAN));
+    }
+    table
+}
+
+/// Label, followed by the request's doc comment on the next lines
+fn format_label(run: &RunCmd) -> String {
+    match &run.doc {
+        Some(doc) => format!("{}\n{}", run.label, doc.dimmed()),
+        None => run.label.clone(),
+    }
+}
+
+/// Status code, followed by the number of attempts when the request was retried,
+/// or the error when the request got no response
+fn format_status(run: &RunCmd) -> String {
+    if let Some(error) = &run.error {
+        return format!("error: {}", error);
+    }
+    match run.attempts.len() {
+        0 | 1 => run.status.to_string(),
+        n => format!("{} ({} attempts)", run.status, n),
+    }
+}
+
+fn format_time(duration_ms: u128) -> String {
+    format!("{} ms", duration_ms)
+}
+
+fn format_size(size: u64) -> String {
+    if size >= 1024 {
+        format!("{:.2} KB", size as f64 / 1024.0)
+    } else {
+        format!("{} B", size)
+    }
+}
 
-        table
-            .with(Style::modern())
-            .with(
-                Modify::new(Rows::first())
-                    .with(Color::FG_CYAN)
-                    .with(Alignment::center()),
-            )
-            .with(Modify::new(Rows::new(1..)).with(Alignment::left()));
+/// Shorten a body to at most [`BODY_PREVIEW_LINES`] lines and
+/// [`BODY_PREVIEW_CHARS`] characters
+fn body_preview(body: &str) -> String {
+    let lines = body.trim().lines().collect::<Vec<_>>();
+    let text = lines
+        .iter()
+        .take(BODY_PREVIEW_LINES)
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut preview: String = text.chars().take(BODY_PREVIEW_CHARS).collect();
 
-        println!("{}", table);
+    if lines.len() > BODY_PREVIEW_LINES || text.chars().count() > BODY_PREVIEW_CHARS {
+        preview.push('…');
     }
+    preview
 }
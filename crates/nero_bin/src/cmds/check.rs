@@ -0,0 +1,21 @@
+use colored::Colorize;
+
+use crate::{cli::args::EnvArgs, cmds::script::Script};
+
+pub struct CheckCmd;
+
+impl CheckCmd {
+    /// Validate a script without sending any request
+    pub fn from_file(file: &str, env: &EnvArgs) -> anyhow::Result<()> {
+        let script = Script::load(file)?;
+        script.check(script.resolver(env)?)?;
+
+        let requests = script
+            .ast
+            .iter()
+            .filter(|s| matches!(s, nero_core::ast::Stmt::Request(_)))
+            .count();
+        println!("{} {} ({} requests)", "OK".green().bold(), file, requests);
+        Ok(())
+    }
+}
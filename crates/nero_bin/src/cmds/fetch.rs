@@ -0,0 +1,122 @@
+use nero_core::{
+    ast::{Body, Expr, ExprKind, Req, StringPart},
+    resolver::Resolver,
+    span::Span,
+    token::HttpMethod,
+};
+use nero_requests::executor::Executor;
+use std::{fs, time::Duration};
+
+use crate::cmds::run::RunCmd;
+
+/// Options of a one-shot `nero fetch` request
+pub struct FetchCmd {
+    pub method: String,
+    pub url: String,
+    /// Request timeout in seconds
+    pub timeout: Option<u64>,
+    /// `-H 'Key: Value'`
+    pub headers: Vec<String>,
+    /// `-q key=value`
+    pub query: Vec<(String, String)>,
+    /// `-d <data>` or `-d @file`, sent as is
+    pub data: Option<String>,
+    /// `--json <json>` or `--json @file`
+    pub json: Option<String>,
+}
+
+impl FetchCmd {
+    /// Build a [`Req`] from the command line and send it through the [`Executor`]
+    pub async fn execute(self) -> anyhow::Result<Vec<RunCmd>> {
+        let headers = self
+            .headers
+            .iter()
+            .map(|h| match h.split_once(':') {
+                Some((k, v)) => Ok((k.trim().to_string(), text(v.trim()))),
+                None => Err(anyhow::anyhow!(
+                    "Invalid header `{}`, expected `Key: Value`",
+                    h
+                )),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let body = match (&self.data, &self.json) {
+            (Some(_), Some(_)) => anyhow::bail!("--data and --json cannot be used together"),
+            (Some(data), None) => Some(Body::Raw(text(&read_data(data)?))),
+            (None, Some(json)) => {
+                let json: serde_json::Value = serde_json::from_str(&read_data(json)?)?;
+                Some(Body::Json(json_expr(json)))
+            }
+            (None, None) => None,
+        };
+
+        let req = Req {
+            label: "fetch".into(),
+            method: HttpMethod::from(self.method.as_str()),
+            url: text(&self.url),
+            headers,
+            query: self
+                .query
+                .iter()
+                .map(|(k, v)| (k.clone(), text(v)))
+                .collect(),
+            body,
+            captures: vec![],
+            asserts: vec![],
+            options: vec![],
+            auth: None,
+            cookies: vec![],
+            doc: None,
+            span: Span::default(),
+        };
+
+        if !req.method.is_valid() {
+            anyhow::bail!("Invalid HTTP method: {}", req.method);
+        }
+
+        let executor = Executor::new().with_timeout(self.timeout.map(Duration::from_secs));
+        let response = executor.execute(&req, &Resolver::new()).await?;
+
+        Ok(vec![RunCmd::from_response(
+            &self.url,
+            &req,
+            response,
+            vec![],
+        )])
+    }
+}
+
+/// Read `@file` arguments from disk, other values are used as is
+fn read_data(data: &str) -> anyhow::Result<String> {
+    match data.strip_prefix('@') {
+        Some(path) => Ok(fs::read_to_string(path)?),
+        None => Ok(data.to_string()),
+    }
+}
+
+/// A plain string expression, without any interpolation
+fn text(s: &str) -> Expr {
+    Expr::new(
+        ExprKind::String(vec![StringPart::Text(s.to_string())]),
+        Span::default(),
+    )
+}
+
+fn json_expr(json: serde_json::Value) -> Expr {
+    let kind = match json {
+        serde_json::Value::Null => ExprKind::Null,
+        serde_json::Value::Bool(b) => ExprKind::Bool(b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(n) => ExprKind::Number(n),
+            None => ExprKind::Float(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        serde_json::Value::String(s) => return text(&s),
+        serde_json::Value::Array(items) => {
+            ExprKind::Array(items.into_iter().map(json_expr).collect())
+        }
+        serde_json::Value::Object(items) => {
+            ExprKind::Object(items.into_iter().map(|(k, v)| (k, json_expr(v))).collect())
+        }
+    };
+    Expr::new(kind, Span::default())
+}
@@ -1 +1,5 @@
+pub mod check;
+pub mod fetch;
 pub mod run;
+pub mod script;
+pub mod test;
@@ -1,69 +1,316 @@
 use nero_core::{
-    ast::Stmt, lexer::Lexer, parser::Parser, resolver::Resolver, semantic::SemanticChecker,
+    ast::{Req, Stmt},
+    graph::DependencyGraph,
+    resolver::{Resolver, ResolverError, Value},
+};
+use nero_requests::{
+    cookies::CookieJar,
+    executor::{Executor, ExecutorError},
+    response::{Attempt, Response},
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+};
+use tokio::task::JoinSet;
+
+use crate::{
+    cli::{
+        args::{EnvArgs, RunArgs},
+        diagnostic::Diagnostic,
+    },
+    cmds::script::Script,
 };
-use nero_requests::executor::Executor;
-use std::{fs, time::Instant};
 
 pub struct RunCmd {
     pub label: String,
+    /// The `///` doc comment written above the request
+    pub doc: Option<String>,
     pub status: u16,
     pub method: String,
+    pub url: String,
     pub size: u64,
     pub headers: Vec<(String, String)>,
     pub body: String,
     pub duration_ms: u128,
     pub file: String,
     pub date: String,
+    pub assertions: Vec<AssertionResult>,
+    /// Every attempt made, more than one when the request was retried
+    pub attempts: Vec<Attempt>,
+    /// Why the request got no response, e.g. a timeout or a refused
+    /// connection. The response fields are empty then.
+    pub error: Option<String>,
+}
+
+/// Outcome of a single line of an `ASSERT` block
+pub struct AssertionResult {
+    /// The assertion as written in the script, e.g. `status == 200`
+    pub expr: String,
+    pub passed: bool,
+    /// Why the assertion failed
+    pub message: Option<String>,
 }
 impl RunCmd {
-    pub async fn from_file(file: &str) -> anyhow::Result<Vec<RunCmd>> {
-        let source_code = fs::read_to_string(file)?;
-        let tokens = Lexer::tokenize(&source_code)?;
-        let ast = Parser::new(tokens).parse()?;
-
-        let mut resolver = Resolver::new();
-        for stmt in &ast {
-            resolver.resolve_statement(stmt)?;
+    /// Run every request of a script, up to `--parallel` at a time.
+    ///
+    /// Requests are scheduled along the script's [`DependencyGraph`]: a
+    /// request starts once every request whose `CAPTURE` it relies on has
+    /// completed. Each request sees the variables it would have seen in a
+    /// sequential run, and results are returned in source order.
+    ///
+    /// A request that gets no response is recorded with [`RunCmd::error`] and
+    /// the run goes on. Requests depending on it are not sent and are
+    /// recorded as failed too.
+    ///
+    /// Cookies are shared by every request of the run, and loaded from and
+    /// saved to `--cookie-jar` when given.
+    pub async fn from_file(
+        file: &str,
+        env: &EnvArgs,
+        run: &RunArgs,
+    ) -> anyhow::Result<Vec<RunCmd>> {
+        let script = Script::load(file)?;
+        let resolver = script.resolver(env)?;
+        script.check(resolver.clone())?;
+
+        let diagnose = |(stmt, e): (usize, ResolverError)| {
+            script.error_at(vec![(stmt, Diagnostic::new(&e, e.span()))])
+        };
+
+        let graph = DependencyGraph::new(&script.ast);
+        let cookies = match &run.cookie_jar {
+            Some(jar) => CookieJar::load(jar).map_err(anyhow::Error::msg)?,
+            None => CookieJar::default(),
+        };
+        let executor = Executor::with_cookies(Arc::new(cookies));
+
+        let mut pending: Vec<usize> = script
+            .ast
+            .iter()
+            .enumerate()
+            .filter(|(_, stmt)| matches!(stmt, Stmt::Request(_)))
+            .map(|(i, _)| i)
+            .collect();
+        let mut captured: HashMap<usize, Vec<(String, Value)>> = HashMap::new();
+        let mut failed: HashSet<usize> = HashSet::new();
+        let mut assigned: HashMap<usize, Value> = HashMap::new();
+        let mut result: BTreeMap<usize, RunCmd> = BTreeMap::new();
+        let mut running = JoinSet::new();
+
+        let scheduled = async {
+            loop {
+                while running.len() < usize::from(run.parallel) {
+                    let Some(pos) = pending.iter().position(|&i| {
+                        graph
+                            .dependencies(i)
+                            .iter()
+                            .all(|d| captured.contains_key(d) || failed.contains(d))
+                    }) else {
+                        break;
+                    };
+                    let index = pending.remove(pos);
+                    let Stmt::Request(req) = &script.ast[index] else {
+                        unreachable!("only requests are scheduled");
+                    };
+
+                    if let Some(&dep) = graph
+                        .dependencies(index)
+                        .iter()
+                        .find(|d| failed.contains(d))
+                    {
+                        let Stmt::Request(dep) = &script.ast[dep] else {
+                            unreachable!("dependencies are requests");
+                        };
+                        failed.insert(index);
+                        let error = format!("not sent, `{}` failed", dep.label);
+                        result.insert(index, RunCmd::from_error(file, req, String::new(), error));
+                        continue;
+                    }
+
+                    let resolver = Self::resolver_at(
+                        &script.ast,
+                        &graph,
+                        &captured,
+                        &mut assigned,
+                        resolver.clone(),
+                        index,
+                    )
+                    .map_err(diagnose)?;
+                    let req = req.clone();
+                    // paths in an imported request are relative to its own file
+                    let executor = executor.clone().with_base_dir(script.dir_of(index));
+                    running.spawn(async move {
+                        let response = executor.execute(&req, &resolver).await;
+                        (index, req, resolver, response)
+                    });
+                }
+
+                let Some(joined) = running.join_next().await else {
+                    break;
+                };
+                let (index, req, mut resolver, response) = joined?;
+                let response = match response {
+                    Ok(response) => response,
+                    Err(ExecutorError::Resolver(e)) => return Err(diagnose((index, e)).into()),
+                    Err(e) => {
+                        let url = resolver
+                            .resolve_expression(&req.url)
+                            .map(|url| url.to_string())
+                            .unwrap_or_default();
+                        failed.insert(index);
+                        result.insert(index, RunCmd::from_error(file, &req, url, e.to_string()));
+                        continue;
+                    }
+                };
+
+                let scope = response.scope();
+                let assertions = req
+                    .asserts
+                    .iter()
+                    .map(|assertion| {
+                        let expr = script
+                            .source_of(index)
+                            .get(assertion.span.start..assertion.span.end)
+                            .unwrap_or_default()
+                            .to_string();
+                        match resolver.resolve_assertion(assertion, &scope) {
+                            Ok((passed, actual)) => AssertionResult {
+                                expr,
+                                passed,
+                                message: (!passed).then(|| format!("got {}", actual)),
+                            },
+                            Err(e) => AssertionResult {
+                                expr,
+                                passed: false,
+                                message: Some(e.to_string()),
+                            },
+                        }
+                    })
+                    .collect();
+
+                resolver
+                    .resolve_captures(&req.captures, &scope)
+                    .map_err(|e| diagnose((index, e)))?;
+                captured.insert(
+                    index,
+                    req.captures
+                        .iter()
+                        .filter_map(|(name, _)| {
+                            Some((name.clone(), resolver.variables.get(name)?.clone()))
+                        })
+                        .collect(),
+                );
+
+                result.insert(
+                    index,
+                    RunCmd::from_response(file, &req, response, assertions),
+                );
+            }
+            Ok::<(), anyhow::Error>(())
+        }
+        .await;
+        if let Err(e) = scheduled {
+            // do not leave requests of a failed run in flight
+            running.abort_all();
+            return Err(e);
+        }
+
+        if let Some(jar) = &run.cookie_jar {
+            executor.cookies.save(jar).map_err(anyhow::Error::msg)?;
         }
 
-        let mut semantic = SemanticChecker::new(&resolver);
-        for stmt in &ast {
-            semantic.check_statement(stmt)?;
+        Ok(result.into_values().collect())
+    }
+
+    /// Rebuild the variables visible to the statement at `index` by replaying
+    /// the statements before it.
+    ///
+    /// Captures of completed requests are applied at their position, and
+    /// assignments waiting on requests that have not completed yet are
+    /// skipped: the graph guarantees the statement at `index` does not use them.
+    ///
+    /// An assignment is evaluated once per run and its value kept in
+    /// `assigned`, so `id = uuid();` is the same for every request.
+    fn resolver_at(
+        ast: &[Stmt],
+        graph: &DependencyGraph,
+        captured: &HashMap<usize, Vec<(String, Value)>>,
+        assigned: &mut HashMap<usize, Value>,
+        mut resolver: Resolver,
+        index: usize,
+    ) -> Result<Resolver, (usize, ResolverError)> {
+        for (i, stmt) in ast[..index].iter().enumerate() {
+            match stmt {
+                Stmt::Request(_) => {
+                    for (name, value) in captured.get(&i).into_iter().flatten() {
+                        resolver.variables.insert(name.clone(), value.clone());
+                    }
+                }
+                Stmt::Assignment { name, .. } if assigned.contains_key(&i) => {
+                    resolver
+                        .variables
+                        .insert(name.clone(), assigned[&i].clone());
+                }
+                _ if graph
+                    .dependencies(i)
+                    .iter()
+                    .all(|d| captured.contains_key(d)) =>
+                {
+                    resolver.resolve_statement(stmt).map_err(|e| (i, e))?;
+                    if let Stmt::Assignment { name, .. } = stmt
+                        && let Some(value) = resolver.variables.get(name)
+                    {
+                        assigned.insert(i, value.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(resolver)
+    }
+
+    pub fn from_response(
+        file: &str,
+        req: &Req,
+        response: Response,
+        assertions: Vec<AssertionResult>,
+    ) -> RunCmd {
+        RunCmd {
+            file: file.to_string(),
+            date: chrono::Utc::now().to_rfc3339(),
+            method: req.method.to_string(),
+            url: response.url,
+            size: response.size,
+            label: req.label.clone(),
+            doc: req.doc.clone(),
+            status: response.status,
+            headers: response.headers,
+            body: response.body,
+            duration_ms: response.duration_ms,
+            attempts: response.attempts,
+            assertions,
+            error: None,
         }
+    }
 
-        let executor = Executor::new(&resolver);
-        let mut result: Vec<RunCmd> = Vec::new();
-
-        for stmt in &ast {
-            let Stmt::Request(req) = stmt else {
-                continue;
-            };
-            let start = Instant::now();
-
-            let response = executor.execute(req).await?;
-
-            let size = response.content_length().unwrap_or(0);
-            let status = response.status().as_u16();
-            let duration_ms = start.elapsed().as_millis();
-            let headers = response
-                .headers()
-                .iter()
-                .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
-                .collect::<Vec<_>>();
-            let body = response.text().await?;
-
-            result.push(RunCmd {
-                file: file.to_string(),
-                date: chrono::Utc::now().to_rfc3339(),
-                method: req.method.clone(),
-                size,
-                label: req.label.clone(),
-                status,
-                headers,
-                body,
-                duration_ms,
-            });
+    /// Result of a request that got no response
+    pub fn from_error(file: &str, req: &Req, url: String, error: String) -> RunCmd {
+        RunCmd {
+            file: file.to_string(),
+            date: chrono::Utc::now().to_rfc3339(),
+            method: req.method.to_string(),
+            url,
+            size: 0,
+            label: req.label.clone(),
+            doc: req.doc.clone(),
+            status: 0,
+            headers: vec![],
+            body: String::new(),
+            duration_ms: 0,
+            attempts: vec![],
+            assertions: vec![],
+            error: Some(error),
         }
-        Ok(result)
     }
 }
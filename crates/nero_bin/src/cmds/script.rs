@@ -0,0 +1,358 @@
+use nero_core::{
+    ast::Stmt,
+    lexer::Lexer,
+    parser::Parser,
+    resolver::{Resolver, ResolverError, Value},
+    semantic::SemanticChecker,
+};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::cli::{
+    args::EnvArgs,
+    diagnostic::{Diagnostic, ScriptError},
+    dotenv,
+};
+
+/// Sidecar file next to a script holding shared `env` blocks
+pub const ENV_FILE: &str = "nero.env.ns";
+
+/// Dotenv file next to a script holding secrets, read through `env.NAME`
+pub const DOTENV_FILE: &str = ".env";
+
+/// A parsed script together with its source, used to render diagnostics
+pub struct Script {
+    pub file: String,
+    pub source: String,
+    /// Statements of the script, with every `import` replaced by the
+    /// statements of the imported file
+    pub ast: Vec<Stmt>,
+    /// For every statement of `ast`, the file it was written in: `0` for
+    /// this script, `n` for `imports[n - 1]`
+    pub origins: Vec<usize>,
+    /// `(file, source)` of every file pulled in with `import`
+    pub imports: Vec<(String, String)>,
+    /// The `nero.env.ns` found next to the script, if any
+    pub env_file: Option<Box<Script>>,
+}
+
+impl Script {
+    /// Read and parse a script along with its imports and the `nero.env.ns`
+    /// next to it
+    pub fn load(file: &str) -> anyhow::Result<Script> {
+        let mut script = Self::parse(file)?;
+
+        let mut sources = vec![(script.file.clone(), script.source.clone())];
+        let mut stack = vec![Self::canonical(file)?];
+        let mut seen = HashSet::new();
+        let ast = std::mem::take(&mut script.ast);
+        let statements = Self::expand(ast, 0, "", &mut sources, &mut stack, &mut seen)?;
+
+        (script.origins, script.ast) = statements.into_iter().unzip();
+        script.imports = sources.split_off(1);
+
+        let path = Path::new(file);
+        let env_file = path.with_file_name(ENV_FILE);
+        if env_file.is_file() && path.file_name() != env_file.file_name() {
+            let env_script = Self::parse(&env_file.to_string_lossy())?;
+
+            let diagnostics: Vec<Diagnostic> = env_script
+                .ast
+                .iter()
+                .filter(|stmt| !matches!(stmt, Stmt::Env { .. }))
+                .map(|stmt| {
+                    Diagnostic::new(
+                        format!("Only env blocks are allowed in {}", ENV_FILE),
+                        stmt.span(),
+                    )
+                })
+                .collect();
+            if !diagnostics.is_empty() {
+                return Err(env_script.error(diagnostics).into());
+            }
+
+            script.env_file = Some(Box::new(env_script));
+        }
+
+        Ok(script)
+    }
+
+    /// Read, tokenize and parse a script, reporting every parser error at once
+    fn parse(file: &str) -> anyhow::Result<Script> {
+        let source = fs::read_to_string(file)?;
+
+        let tokens = Lexer::tokenize(&source)
+            .map_err(|e| ScriptError::new(file, &source, vec![Diagnostic::new(&e, e.span())]))?;
+
+        let (ast, errors) = Parser::new(tokens).parse_all();
+        if !errors.is_empty() {
+            let diagnostics = errors.iter().map(|e| Diagnostic::new(e, e.span()));
+            return Err(ScriptError::new(file, &source, diagnostics.collect()).into());
+        }
+
+        Ok(Script {
+            file: file.to_string(),
+            source,
+            origins: vec![0; ast.len()],
+            ast,
+            imports: Vec::new(),
+            env_file: None,
+        })
+    }
+
+    fn canonical(file: &str) -> anyhow::Result<PathBuf> {
+        fs::canonicalize(file).map_err(|e| anyhow::anyhow!("{}: {}", file, e))
+    }
+
+    /// Replace every `import` in `ast` with the statements of the imported
+    /// file, recursively.
+    ///
+    /// Paths are relative to the importing file. Labels of imported requests
+    /// are prefixed with the file stem, e.g. `#[login]` in `common.ns` becomes
+    /// `common.login`. A file imported a second time is skipped, and a file
+    /// importing itself, directly or not, is an error.
+    ///
+    /// `origin` is the index in `sources` of the file `ast` was parsed from,
+    /// and `stack` the chain of files currently being imported.
+    fn expand(
+        ast: Vec<Stmt>,
+        origin: usize,
+        namespace: &str,
+        sources: &mut Vec<(String, String)>,
+        stack: &mut Vec<PathBuf>,
+        seen: &mut HashSet<PathBuf>,
+    ) -> anyhow::Result<Vec<(usize, Stmt)>> {
+        let mut statements = Vec::new();
+
+        for stmt in ast {
+            let error = |sources: &[(String, String)], message: String, span| {
+                let (file, source) = &sources[origin];
+                ScriptError::new(file, source, vec![Diagnostic::new(message, span)])
+            };
+
+            let (path, span) = match stmt {
+                Stmt::Import { path, span } => (path, span),
+                Stmt::Env { name, span, .. } if origin != 0 => {
+                    let message = format!(
+                        "env block `{}` cannot be imported, move it to {}",
+                        name, ENV_FILE
+                    );
+                    return Err(error(sources, message, span).into());
+                }
+                Stmt::Request(mut req) => {
+                    if !namespace.is_empty() {
+                        req.label = format!("{}.{}", namespace, req.label);
+                    }
+                    statements.push((origin, Stmt::Request(req)));
+                    continue;
+                }
+                stmt => {
+                    statements.push((origin, stmt));
+                    continue;
+                }
+            };
+
+            let target = Path::new(&sources[origin].0).with_file_name(&path);
+            let canonical = fs::canonicalize(&target)
+                .map_err(|e| error(sources, format!("Cannot import `{}`: {}", path, e), span))?;
+
+            if let Some(pos) = stack.iter().position(|f| *f == canonical) {
+                let chain: Vec<String> = stack[pos..]
+                    .iter()
+                    .chain([&canonical])
+                    .map(|f| f.to_string_lossy().to_string())
+                    .collect();
+                let message = format!("Import cycle: {}", chain.join(" -> "));
+                return Err(error(sources, message, span).into());
+            }
+            if !seen.insert(canonical.clone()) {
+                continue;
+            }
+
+            let mut imported = Self::parse(&target.to_string_lossy())?;
+            let stem = Path::new(&path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or(path);
+            let namespace = if namespace.is_empty() {
+                stem
+            } else {
+                format!("{}.{}", namespace, stem)
+            };
+
+            sources.push((imported.file, imported.source));
+            let index = sources.len() - 1;
+
+            stack.push(canonical);
+            let ast = std::mem::take(&mut imported.ast);
+            statements.extend(Self::expand(ast, index, &namespace, sources, stack, seen)?);
+            stack.pop();
+        }
+
+        Ok(statements)
+    }
+
+    /// `(file, source)` of the file the statement at `index` was written in
+    fn origin_of(&self, index: usize) -> (&str, &str) {
+        match self.origins.get(index).copied().unwrap_or(0) {
+            0 => (&self.file, &self.source),
+            n => {
+                let (file, source) = &self.imports[n - 1];
+                (file, source)
+            }
+        }
+    }
+
+    /// Source of the file the statement at `index` was written in, which is
+    /// what the spans of that statement point into
+    pub fn source_of(&self, index: usize) -> &str {
+        self.origin_of(index).1
+    }
+
+    /// Directory of the file the statement at `index` was written in, paths
+    /// inside that statement are relative to it
+    pub fn dir_of(&self, index: usize) -> Option<PathBuf> {
+        Path::new(self.origin_of(index).0)
+            .parent()
+            .map(Path::to_path_buf)
+    }
+
+    pub fn error(&self, diagnostics: Vec<Diagnostic>) -> ScriptError {
+        ScriptError::new(&self.file, &self.source, diagnostics)
+    }
+
+    /// Build an error from diagnostics paired with the index of the statement
+    /// they belong to, so each is rendered against the file it was written in
+    pub fn error_at(&self, diagnostics: Vec<(usize, Diagnostic)>) -> ScriptError {
+        let sources = [(self.file.clone(), self.source.clone())]
+            .into_iter()
+            .chain(self.imports.iter().cloned())
+            .collect();
+        let diagnostics = diagnostics
+            .into_iter()
+            .map(|(stmt, d)| (self.origins.get(stmt).copied().unwrap_or(0), d))
+            .collect();
+        ScriptError::with_sources(sources, diagnostics)
+    }
+
+    /// Build the resolver a run starts from.
+    ///
+    /// The selected `env` block is resolved first, `nero.env.ns` before the
+    /// script's own block so the script wins on conflicts. `--var` values
+    /// are stored as overrides that no assignment can replace.
+    ///
+    /// Variables from the `.env` next to the script are merged into the
+    /// resolver's copy of the environment, read by `env.NAME` and `env()`.
+    /// They do not override variables that are already set in the process
+    /// environment.
+    pub fn resolver(&self, env: &EnvArgs) -> anyhow::Result<Resolver> {
+        let mut resolver = Resolver::new();
+
+        let dotenv = Path::new(&self.file).with_file_name(DOTENV_FILE);
+        if dotenv.is_file() {
+            for (key, value) in dotenv::parse(&fs::read_to_string(dotenv)?) {
+                resolver.env_vars.entry(key).or_insert(value);
+            }
+        }
+
+        resolver.overrides = env
+            .vars
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect();
+
+        let Some(selected) = &env.env else {
+            return Ok(resolver);
+        };
+
+        let scripts = self.env_file.as_deref().into_iter().chain([self]);
+        let mut found = false;
+        let mut available: Vec<&str> = Vec::new();
+
+        for script in scripts {
+            for stmt in &script.ast {
+                let Stmt::Env {
+                    name, assignments, ..
+                } = stmt
+                else {
+                    continue;
+                };
+                if !available.contains(&name.as_str()) {
+                    available.push(name);
+                }
+                if name != selected {
+                    continue;
+                }
+
+                found = true;
+                resolver
+                    .resolve_env(assignments)
+                    .map_err(|e| script.error(vec![Diagnostic::new(&e, e.span())]))?;
+            }
+        }
+
+        if !found {
+            anyhow::bail!(
+                "Unknown environment `{}`, available: {}",
+                selected,
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            );
+        }
+
+        Ok(resolver)
+    }
+
+    /// Statically resolve every variable and run the semantic checks on the
+    /// script, without sending any request.
+    ///
+    /// Variables that depend on a `CAPTURE` are only known once the request
+    /// has run, so they are skipped here and checked at execution time.
+    pub fn check(&self, mut resolver: Resolver) -> Result<(), ScriptError> {
+        let mut deferred: HashSet<String> = HashSet::new();
+        let mut diagnostics: Vec<(usize, Diagnostic)> = Vec::new();
+
+        for (i, stmt) in self.ast.iter().enumerate() {
+            match resolver.resolve_statement(stmt) {
+                Err(ResolverError::UndefinedVariable(name, _)) if deferred.contains(&name) => {
+                    if let Stmt::Assignment { name, .. } = stmt {
+                        deferred.insert(name.clone());
+                    }
+                }
+                Err(e) => diagnostics.push((i, Diagnostic::new(&e, e.span()))),
+                Ok(()) => {}
+            }
+
+            if let Stmt::Request(req) = stmt {
+                deferred.extend(req.captures.iter().map(|(name, _)| name.clone()));
+            }
+        }
+
+        // an unknown function or filter is reported by both passes, keep one
+        let mut semantic = SemanticChecker::new(&resolver);
+        for (i, stmt) in self.ast.iter().enumerate() {
+            let Err(e) = semantic.check_statement(stmt) else {
+                continue;
+            };
+            let diagnostic = Diagnostic::new(&e, e.span());
+            let duplicate = diagnostics
+                .iter()
+                .any(|(_, d)| d.span == diagnostic.span && d.message == diagnostic.message);
+            if !duplicate {
+                diagnostics.push((i, diagnostic));
+            }
+        }
+
+        if !diagnostics.is_empty() {
+            return Err(self.error_at(diagnostics));
+        }
+
+        Ok(())
+    }
+}
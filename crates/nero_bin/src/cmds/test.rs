@@ -0,0 +1,35 @@
+use crate::{
+    cli::{
+        args::{EnvArgs, RunArgs},
+        output::OutputPrint,
+    },
+    cmds::run::{AssertionResult, RunCmd},
+};
+
+pub struct TestCmd;
+
+impl TestCmd {
+    /// Run a script and check every `ASSERT` block.
+    ///
+    /// A request without an `ASSERT` block passes when its status is below 400,
+    /// a request that got no response always fails.
+    ///
+    /// # Return
+    /// `true` if every assertion passed
+    pub async fn from_file(file: &str, env: &EnvArgs, run: &RunArgs) -> anyhow::Result<bool> {
+        let mut result = RunCmd::from_file(file, env, run).await?;
+
+        for run in &mut result {
+            if run.assertions.is_empty() && run.error.is_none() {
+                let passed = run.status < 400;
+                run.assertions.push(AssertionResult {
+                    expr: "status < 400".into(),
+                    passed,
+                    message: (!passed).then(|| format!("got {}", run.status)),
+                });
+            }
+        }
+
+        Ok(OutputPrint::test(file, &result))
+    }
+}
@@ -1,36 +1,63 @@
-mod cli;
-mod cmds;
-
 use clap::Parser;
-use cli::args;
-
-use crate::{
-    cli::{args::RunOutputType, output::OutputPrint},
-    cmds::run::RunCmd,
+use nero_bin::{
+    cli::{args, output::OutputPrint},
+    cmds::{check::CheckCmd, fetch::FetchCmd, run::RunCmd, test::TestCmd},
 };
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() {
     let cli = args::NeroArgs::parse();
+    if let Err(err) = run(cli).await {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: args::NeroArgs) -> Result<(), Box<dyn std::error::Error>> {
     #[allow(unused)]
     match cli.command {
         args::Commands::Compile { file } => {
             println!("WIP");
         }
+        args::Commands::Test { file, run, env } => {
+            if !TestCmd::from_file(&file, &env, &run).await? {
+                std::process::exit(1);
+            }
+        }
+        args::Commands::Check { file, env } => {
+            CheckCmd::from_file(&file, &env)?;
+        }
         args::Commands::Fetch {
             method,
             timeout,
+            headers,
+            query,
+            data,
+            json,
+            output,
             url,
         } => {
-            println!("WIP");
-        }
-        args::Commands::Run { file, output } => {
-            let response = RunCmd::from_file(&file).await?;
-            match output {
-                RunOutputType::Json => OutputPrint::json(&response),
-                RunOutputType::Summary => OutputPrint::summary(&response),
-                RunOutputType::Table => OutputPrint::table(&response),
+            let response = FetchCmd {
+                method,
+                url: url.clone(),
+                timeout,
+                headers,
+                query,
+                data,
+                json,
             }
+            .execute()
+            .await?;
+            OutputPrint::print(&output, &url, &response);
+        }
+        args::Commands::Run {
+            file,
+            output,
+            run,
+            env,
+        } => {
+            let response = RunCmd::from_file(&file, &env, &run).await?;
+            OutputPrint::print(&output, &file, &response);
         }
     }
 
@@ -0,0 +1,67 @@
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    process::Command,
+    thread,
+};
+
+/// Serve `status` to every request on a local port
+fn serve(status: u16) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let _ = stream.read(&mut [0; 4096]);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {} Stub\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                status
+            );
+        }
+    });
+    url
+}
+
+fn nero_test(name: &str, script: &str, url: &str) -> Option<i32> {
+    let file = std::env::temp_dir().join(format!("nero-exit-{}-{}.ns", name, std::process::id()));
+    std::fs::write(&file, script).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_nero"))
+        .arg("test")
+        .arg(&file)
+        .args(["--var", &format!("url={}", url)])
+        .output()
+        .unwrap();
+    output.status.code()
+}
+
+#[test]
+fn test_exits_with_failure() {
+    let url = serve(500);
+    let script = r#"#[health] @GET "${url}/health" { ASSERT { status == 200 } }"#;
+    assert_eq!(nero_test("fail", script, &url), Some(1));
+
+    let script = r#"#[health] @GET "${url}/health" { ASSERT { status == 500 } }"#;
+    assert_eq!(nero_test("pass", script, &url), Some(0));
+}
+
+#[test]
+fn check_exits_with_failure() {
+    let check = |name: &str, script: &str| {
+        let file =
+            std::env::temp_dir().join(format!("nero-check-{}-{}.ns", name, std::process::id()));
+        std::fs::write(&file, script).unwrap();
+        Command::new(env!("CARGO_BIN_EXE_nero"))
+            .arg("check")
+            .arg(&file)
+            .output()
+            .unwrap()
+    };
+
+    let output = check("ok", r#"#[health] @GET "http://localhost/health" {}"#);
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = check("bad", "x = { a: @ };\n@GET \"url\" {}\nz = ;\n");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.matches(" --> ").count(), 3, "{}", stderr);
+}
@@ -1,18 +1,277 @@
+use crate::{span::Span, token::HttpMethod};
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Req {
     pub label: String,
-    pub method: String,
+    pub method: HttpMethod,
     pub url: Expr,
     pub headers: Vec<(String, Expr)>,
     pub query: Vec<(String, Expr)>,
-    pub body: Option<Vec<(String, Expr)>>,
+    pub body: Option<Body>,
+    /// Variabel yang diambil dari response, contoh: `token = body.data.token;`
+    pub captures: Vec<(String, Expr)>,
+    /// Assertion terhadap response, contoh: `status == 200`
+    pub asserts: Vec<Assertion>,
+    /// Pengaturan request, contoh: `timeout: 5000`.
+    /// Lihat [`crate::options::RequestOptions`]
+    pub options: Vec<(String, Expr)>,
+    /// Skema autentikasi beserta nilainya, contoh: `bearer: token`.
+    /// Lihat [`crate::auth::Auth`]
+    pub auth: Option<(String, Expr)>,
+    /// Cookie yang dikirim bersama request ini, contoh: `"session": token`
+    pub cookies: Vec<(String, Expr)>,
+    /// Doc comment `///` di atas request, baris-barisnya digabung dengan `\n`
+    pub doc: Option<String>,
+    /// Lokasi request, dari `#[label]` sampai `}` penutup
+    pub span: Span,
+}
+
+impl Req {
+    /// Semua expression di dalam request: url, header, query, cookie, body,
+    /// capture, option, auth dan kedua sisi assertion
+    pub fn expressions(&self) -> Vec<&Expr> {
+        let mut exprs = vec![&self.url];
+        exprs.extend(self.headers.iter().map(|(_, v)| v));
+        exprs.extend(self.query.iter().map(|(_, v)| v));
+        exprs.extend(self.cookies.iter().map(|(_, v)| v));
+        match &self.body {
+            Some(Body::Json(expr) | Body::Raw(expr) | Body::File { path: expr, .. }) => {
+                exprs.push(expr)
+            }
+            Some(Body::Form(fields)) => exprs.extend(fields.iter().map(|(_, v)| v)),
+            Some(Body::Multipart(parts)) => {
+                exprs.extend(parts.iter().map(|(_, part)| match part {
+                    Part::Text(expr) | Part::File(expr) => expr,
+                }))
+            }
+            None => {}
+        }
+        exprs.extend(self.captures.iter().map(|(_, v)| v));
+        exprs.extend(self.options.iter().map(|(_, v)| v));
+        exprs.extend(self.auth.iter().map(|(_, v)| v));
+        for assertion in &self.asserts {
+            exprs.push(&assertion.left);
+            exprs.push(&assertion.right);
+        }
+        exprs
+    }
 }
 
+/// Body sebuah request
 #[derive(Debug, PartialEq, Clone)]
-pub enum Expr {
+pub enum Body {
+    /// Body JSON, contoh: `BODY { "name": "john" }` atau `BODY json { ... }`
+    Json(Expr),
+    /// Body berupa teks apa adanya, contoh: `BODY raw "text"`
+    Raw(Expr),
+    /// Body `application/x-www-form-urlencoded`, contoh: `BODY form { "name": "john" }`
+    Form(Vec<(String, Expr)>),
+    /// Body `multipart/form-data`, contoh:
+    /// `BODY multipart { "name": "john", "avatar": file("./a.png") }`
+    Multipart(Vec<(String, Part)>),
+    /// Isi file sebagai body, contoh: `BODY file("payloads/user.json")`.
+    /// Path relatif terhadap script, dan `${var}` di dalam file diganti
+    /// jika `interpolate` bernilai `true`
+    File { path: Expr, interpolate: bool },
+}
+
+/// Satu bagian body multipart
+#[derive(Debug, PartialEq, Clone)]
+pub enum Part {
+    Text(Expr),
+    /// Isi file dari `file("path")`, path relatif terhadap script
+    File(Expr),
+}
+
+/// Operator perbandingan yang dipakai di block `ASSERT`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CompareOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Contains,
+}
+
+impl std::fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            CompareOp::Eq => "==",
+            CompareOp::NotEq => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::LtEq => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::GtEq => ">=",
+            CompareOp::Contains => "contains",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+/// Operator biner di dalam expression, contoh: `page + 1` atau `a && b`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    /// Perbandingan, sama seperti di `ASSERT` kecuali `contains`
+    Compare(CompareOp),
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    /// Urutan prioritas operator, semakin besar semakin dulu dievaluasi
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Compare(CompareOp::Eq | CompareOp::NotEq) => 3,
+            BinaryOp::Compare(_) => 4,
+            BinaryOp::Add | BinaryOp::Sub => 5,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => 6,
+        }
+    }
+}
+
+impl std::fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+            BinaryOp::Compare(op) => return write!(f, "{}", op),
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+/// Operator unary, contoh: `-offset` atau `!done`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+impl std::fmt::Display for UnaryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnaryOp::Neg => write!(f, "-"),
+            UnaryOp::Not => write!(f, "!"),
+        }
+    }
+}
+
+/// Satu baris assertion, contoh: `body.items.length > 0`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Assertion {
+    pub left: Expr,
+    pub op: CompareOp,
+    pub right: Expr,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    /// Nama variabel yang dipakai di dalam expression, termasuk yang ada di
+    /// dalam template string. Nama fungsi pada `Call` tidak ikut dihitung.
+    pub fn identifiers(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.walk(&mut |expr| {
+            if let ExprKind::Identifier(name) = &expr.kind {
+                names.push(name.as_str());
+            }
+        });
+        names
+    }
+
+    /// Memanggil `f` untuk expression ini dan setiap expression di dalamnya,
+    /// termasuk yang ada di dalam template string
+    pub fn walk<'a>(&'a self, f: &mut impl FnMut(&'a Expr)) {
+        f(self);
+        match &self.kind {
+            ExprKind::String(parts) => {
+                for part in parts {
+                    if let StringPart::Expression(expr) = part {
+                        expr.walk(f);
+                    }
+                }
+            }
+            ExprKind::Array(items) | ExprKind::Call(_, items) => {
+                for item in items {
+                    item.walk(f);
+                }
+            }
+            ExprKind::Object(fields) => {
+                for (_, value) in fields {
+                    value.walk(f);
+                }
+            }
+            ExprKind::Member(base, _) | ExprKind::Unary(_, base) => base.walk(f),
+            ExprKind::Index(base, index) => {
+                base.walk(f);
+                index.walk(f);
+            }
+            ExprKind::Binary(left, _, right) => {
+                left.walk(f);
+                right.walk(f);
+            }
+            ExprKind::Filter(input, _, args) => {
+                input.walk(f);
+                for arg in args {
+                    arg.walk(f);
+                }
+            }
+            ExprKind::Identifier(_)
+            | ExprKind::Number(_)
+            | ExprKind::Float(_)
+            | ExprKind::Bool(_)
+            | ExprKind::Null => {}
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ExprKind {
     Identifier(String),
     Number(i64),
+    Float(f64),
+    Bool(bool),
+    Null,
     String(Vec<StringPart>),
+    /// Array JSON, contoh: `[1, "a", true]`
+    Array(Vec<Expr>),
+    /// Object JSON, contoh: `{ "a": 1, "b": [] }`
+    Object(Vec<(String, Expr)>),
+    /// Akses field, contoh: `body.data` atau `header."content-type"`
+    Member(Box<Expr>, String),
+    /// Akses index array, contoh: `body.items[0]`
+    Index(Box<Expr>, Box<Expr>),
+    /// Pemanggilan fungsi, contoh: `env("API_KEY", "default")`
+    Call(String, Vec<Expr>),
+    /// Operasi biner, contoh: `page + 1` atau `"Bearer " + token`
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    /// Operasi unary, contoh: `!done`
+    Unary(UnaryOp, Box<Expr>),
+    /// Filter beserta argumennya, contoh: `name | upper` atau `id | default("0")`
+    Filter(Box<Expr>, String, Vec<Expr>),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -23,6 +282,35 @@ pub enum StringPart {
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Stmt {
-    Assignment { name: String, value: Expr },
-    Request(Req),
+    Assignment {
+        name: String,
+        value: Expr,
+        span: Span,
+    },
+    Request(Box<Req>),
+    /// Block environment, contoh: `env staging { url = "..."; }`
+    Env {
+        name: String,
+        assignments: Vec<(String, Expr)>,
+        span: Span,
+    },
+    /// Memuat file lain, contoh: `import "common.ns";`
+    ///
+    /// `path` relatif terhadap file yang melakukan import.
+    Import {
+        path: String,
+        span: Span,
+    },
+}
+
+impl Stmt {
+    /// Lokasi statement di dalam source code
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Assignment { span, .. } | Stmt::Env { span, .. } | Stmt::Import { span, .. } => {
+                *span
+            }
+            Stmt::Request(req) => req.span,
+        }
+    }
 }
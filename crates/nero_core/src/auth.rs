@@ -0,0 +1,202 @@
+use crate::resolver::Value;
+
+/// Tempat api key dikirim
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ApiKeyLocation {
+    Header,
+    Query,
+}
+
+/// Grant OAuth2 yang didukung
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum OAuth2Grant {
+    ClientCredentials,
+    Password,
+}
+
+impl OAuth2Grant {
+    /// Nilai `grant_type` yang dikirim ke token endpoint
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ClientCredentials => "client_credentials",
+            Self::Password => "password",
+        }
+    }
+}
+
+/// Pengaturan `AUTH { oauth2: {...} }`
+#[derive(Debug, PartialEq, Clone)]
+pub struct OAuth2 {
+    pub grant: OAuth2Grant,
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub scope: Option<String>,
+    /// Hanya untuk grant `password`
+    pub username: Option<String>,
+    /// Hanya untuk grant `password`
+    pub password: Option<String>,
+    /// Kirim kredensial client di body form, bukan lewat header Basic
+    pub client_auth_body: bool,
+    /// File untuk menyimpan token di luar memory, relatif terhadap script
+    pub cache: Option<String>,
+}
+
+/// Pengaturan `AUTH { sigv4: {...} }`
+#[derive(Debug, PartialEq, Clone)]
+pub struct SigV4 {
+    pub region: String,
+    pub service: String,
+    pub access_key: String,
+    pub secret_key: String,
+    /// Dikirim sebagai `X-Amz-Security-Token` untuk kredensial sementara
+    pub session_token: Option<String>,
+}
+
+/// Skema autentikasi dari section `AUTH`, contoh:
+///
+/// ```text
+/// AUTH { basic: { user: "john", pass: "secret" } }
+/// AUTH { bearer: token }
+/// AUTH { digest: { user: "john", pass: "secret" } }
+/// AUTH { apikey: { in: "query", name: "api_key", value: key } }
+/// AUTH { oauth2: { grant: "client_credentials", token_url: "...", client_id: "...", client_secret: "..." } }
+/// AUTH { sigv4: { region: "us-east-1", service: "execute-api", access_key: "...", secret_key: "..." } }
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub enum Auth {
+    Basic {
+        user: String,
+        pass: String,
+    },
+    Bearer(String),
+    /// Digest auth (RFC 7616), dikirim setelah server membalas 401
+    Digest {
+        user: String,
+        pass: String,
+    },
+    ApiKey {
+        location: ApiKeyLocation,
+        name: String,
+        value: String,
+    },
+    /// Token diambil dari token endpoint lalu dikirim sebagai Bearer
+    OAuth2(OAuth2),
+    /// AWS Signature Version 4, ditandatangani tepat sebelum request dikirim
+    SigV4(SigV4),
+}
+
+impl Auth {
+    /// Nama skema yang dikenali
+    pub const SCHEMES: [&str; 6] = ["basic", "bearer", "digest", "apikey", "oauth2", "sigv4"];
+
+    /// Membuat [`Auth`] dari skema dan nilainya yang sudah di-resolve
+    ///
+    /// # Return
+    /// pesan error jika skema tidak dikenal atau field-nya tidak lengkap
+    pub fn new(scheme: &str, value: &Value) -> Result<Auth, String> {
+        match scheme {
+            "basic" => Ok(Auth::Basic {
+                user: field(scheme, value, "user")?,
+                pass: field(scheme, value, "pass")?,
+            }),
+            "bearer" => match value {
+                Value::String(token) => Ok(Auth::Bearer(token.clone())),
+                _ => Err("`bearer` must be a string token".into()),
+            },
+            "digest" => Ok(Auth::Digest {
+                user: field(scheme, value, "user")?,
+                pass: field(scheme, value, "pass")?,
+            }),
+            "apikey" => {
+                let location = match optional_field(scheme, value, "in")?.as_deref() {
+                    None | Some("header") => ApiKeyLocation::Header,
+                    Some("query") => ApiKeyLocation::Query,
+                    Some(other) => {
+                        return Err(format!(
+                            "`apikey.in` must be \"header\" or \"query\", found \"{}\"",
+                            other
+                        ));
+                    }
+                };
+                Ok(Auth::ApiKey {
+                    location,
+                    name: field(scheme, value, "name")?,
+                    value: field(scheme, value, "value")?,
+                })
+            }
+            "oauth2" => {
+                let grant = match field(scheme, value, "grant")?.as_str() {
+                    "client_credentials" => OAuth2Grant::ClientCredentials,
+                    "password" => OAuth2Grant::Password,
+                    other => {
+                        return Err(format!(
+                            "`oauth2.grant` must be \"client_credentials\" or \"password\", found \"{}\"",
+                            other
+                        ));
+                    }
+                };
+                let (username, password) = match grant {
+                    OAuth2Grant::Password => (
+                        Some(field(scheme, value, "username")?),
+                        Some(field(scheme, value, "password")?),
+                    ),
+                    OAuth2Grant::ClientCredentials => (None, None),
+                };
+                let client_auth_body =
+                    match optional_field(scheme, value, "client_auth")?.as_deref() {
+                        None | Some("basic") => false,
+                        Some("body") => true,
+                        Some(other) => {
+                            return Err(format!(
+                                "`oauth2.client_auth` must be \"basic\" or \"body\", found \"{}\"",
+                                other
+                            ));
+                        }
+                    };
+
+                Ok(Auth::OAuth2(OAuth2 {
+                    grant,
+                    token_url: field(scheme, value, "token_url")?,
+                    client_id: field(scheme, value, "client_id")?,
+                    client_secret: optional_field(scheme, value, "client_secret")?,
+                    scope: optional_field(scheme, value, "scope")?,
+                    username,
+                    password,
+                    client_auth_body,
+                    cache: optional_field(scheme, value, "cache")?,
+                }))
+            }
+            "sigv4" => Ok(Auth::SigV4(SigV4 {
+                region: field(scheme, value, "region")?,
+                service: field(scheme, value, "service")?,
+                access_key: field(scheme, value, "access_key")?,
+                secret_key: field(scheme, value, "secret_key")?,
+                session_token: optional_field(scheme, value, "session_token")?,
+            })),
+            _ => Err(format!(
+                "Unknown auth scheme `{}`, expected one of: {}",
+                scheme,
+                Self::SCHEMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Field wajib bertipe string atau angka dari object `value`
+fn field(scheme: &str, value: &Value, key: &str) -> Result<String, String> {
+    optional_field(scheme, value, key)?
+        .ok_or_else(|| format!("`{}` requires a `{}` field", scheme, key))
+}
+
+fn optional_field(scheme: &str, value: &Value, key: &str) -> Result<Option<String>, String> {
+    let Value::Object(fields) = value else {
+        return Err(format!("`{}` must be an object", scheme));
+    };
+
+    match fields.iter().find(|(k, _)| k == key).map(|(_, v)| v) {
+        None => Ok(None),
+        Some(v @ (Value::String(_) | Value::Number(_))) => Ok(Some(v.to_string())),
+        Some(_) => Err(format!("`{}.{}` must be a string", scheme, key)),
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use crate::{
+    resolver::{Resolver, ResolverError, Value},
+    span::Span,
+    stdlib::{Builtin, Function, ParamType},
+};
+
+/// Daftar filter yang bisa dipakai setelah `|`, contoh: `${name | upper}`
+///
+/// filter adalah [`Function`] yang menerima nilai sebelum `|` sebagai
+/// argumen pertama, sehingga `id | default("0")` sama dengan
+/// `default(id, "0")`. Filter bisa dirangkai: `${name | trim | upper}`.
+///
+/// berisi filter bawaan berikut, dan bisa ditambah dengan [`Filters::register`]:
+///
+/// ```text
+/// upper               huruf besar
+/// lower               huruf kecil
+/// trim                hapus whitespace di awal dan akhir
+/// json                tulis sebagai JSON, string ikut diberi tanda kutip
+/// default(fallback)   `fallback` jika nilainya null atau tidak terdefinisi
+/// ```
+///
+/// nama yang tidak ada di daftar ini dicari di [`crate::stdlib::Stdlib`],
+/// misalnya `${q | urlencode}` atau `${body | sha256}`.
+#[derive(Debug, Clone)]
+pub struct Filters {
+    filters: HashMap<&'static str, Function>,
+}
+
+impl Default for Filters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Filters {
+    /// Filters yang berisi semua filter bawaan
+    pub fn new() -> Self {
+        let mut filters = Self {
+            filters: HashMap::new(),
+        };
+
+        use ParamType::*;
+        let builtins: [(&'static str, &'static [ParamType], usize, Builtin); 5] = [
+            ("upper", &[String], 1, upper),
+            ("lower", &[String], 1, lower),
+            ("trim", &[String], 1, trim),
+            ("json", &[Any], 1, json),
+            ("default", &[Any, Any], 2, default),
+        ];
+        for (name, params, required, call) in builtins {
+            filters.register(Function {
+                name,
+                params,
+                required,
+                call,
+            });
+        }
+
+        filters
+    }
+
+    /// Menambahkan filter, menimpa filter lain dengan nama yang sama
+    pub fn register(&mut self, filter: Function) {
+        self.filters.insert(filter.name, filter);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Function> {
+        self.filters.get(name)
+    }
+}
+
+fn upper(_: &Resolver, args: &[Value], _: Span) -> Result<Value, ResolverError> {
+    let [Value::String(text)] = args else {
+        unreachable!("checked by Function::check_args");
+    };
+    Ok(Value::String(text.to_uppercase()))
+}
+
+fn lower(_: &Resolver, args: &[Value], _: Span) -> Result<Value, ResolverError> {
+    let [Value::String(text)] = args else {
+        unreachable!("checked by Function::check_args");
+    };
+    Ok(Value::String(text.to_lowercase()))
+}
+
+fn trim(_: &Resolver, args: &[Value], _: Span) -> Result<Value, ResolverError> {
+    let [Value::String(text)] = args else {
+        unreachable!("checked by Function::check_args");
+    };
+    Ok(Value::String(text.trim().to_string()))
+}
+
+fn json(_: &Resolver, args: &[Value], _: Span) -> Result<Value, ResolverError> {
+    Ok(Value::String(args[0].to_json().to_string()))
+}
+
+fn default(_: &Resolver, args: &[Value], _: Span) -> Result<Value, ResolverError> {
+    match args {
+        [Value::Null, fallback] => Ok(fallback.clone()),
+        [value, _] => Ok(value.clone()),
+        _ => unreachable!("checked by Function::check_args"),
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    ptr,
+};
+
+use crate::ast::{Body, Expr, Req, Stmt};
+
+/// Nama yang diisi dari response di `CAPTURE` dan `ASSERT`
+const RESPONSE_SCOPE: [&str; 4] = ["status", "header", "body", "duration"];
+/// Nama yang diisi dari request di `HEADERS` dan `QUERY`
+const REQUEST_SCOPE: [&str; 1] = ["request"];
+
+/// Ketergantungan antar statement di dalam script.
+///
+/// Sebuah statement bergantung pada request yang `CAPTURE`-nya menghasilkan
+/// variabel yang ia pakai, baik secara langsung maupun lewat assignment di
+/// antaranya. Request yang tidak saling bergantung boleh dijalankan
+/// bersamaan.
+///
+/// ```text
+/// #[login] @POST "/login" { CAPTURE { token = body.token; } }
+/// auth = "Bearer ${token}";                 // bergantung pada login
+/// #[me] @GET "/me" { HEADERS { "Authorization": auth } }   // bergantung pada login
+/// #[health] @GET "/health" {}               // tidak bergantung pada apapun
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DependencyGraph {
+    /// Untuk setiap statement (sesuai index di AST), index statement request
+    /// yang harus selesai lebih dulu, terurut naik
+    pub deps: Vec<Vec<usize>>,
+}
+
+impl DependencyGraph {
+    pub fn new(ast: &[Stmt]) -> Self {
+        // variabel -> request yang menentukan nilainya
+        let mut producers: HashMap<&str, BTreeSet<usize>> = HashMap::new();
+        let mut deps = Vec::with_capacity(ast.len());
+
+        for (i, stmt) in ast.iter().enumerate() {
+            let names = match stmt {
+                Stmt::Assignment { value, .. } => value.identifiers(),
+                Stmt::Request(req) => Self::request_identifiers(req),
+                Stmt::Env { .. } | Stmt::Import { .. } => vec![],
+            };
+
+            let mut stmt_deps: BTreeSet<usize> = names
+                .into_iter()
+                .filter_map(|name| producers.get(name))
+                .flatten()
+                .copied()
+                .collect();
+
+            // variabel di dalam file body baru diketahui saat request
+            // dijalankan, jadi tunggu semua request yang menghasilkan variabel
+            if let Stmt::Request(req) = stmt
+                && let Some(Body::File {
+                    interpolate: true, ..
+                }) = req.body
+            {
+                stmt_deps.extend(producers.values().flatten());
+            }
+
+            match stmt {
+                Stmt::Assignment { name, .. } if stmt_deps.is_empty() => {
+                    producers.remove(name.as_str());
+                }
+                Stmt::Assignment { name, .. } => {
+                    producers.insert(name, stmt_deps.clone());
+                }
+                Stmt::Request(req) => {
+                    for (name, _) in &req.captures {
+                        producers.insert(name, BTreeSet::from([i]));
+                    }
+                }
+                Stmt::Env { .. } | Stmt::Import { .. } => {}
+            }
+
+            deps.push(stmt_deps.into_iter().collect());
+        }
+
+        Self { deps }
+    }
+
+    /// Nama variabel yang dipakai request, tanpa nama yang ditimpa oleh
+    /// response atau request itu sendiri, misalnya `body` di `CAPTURE`
+    fn request_identifiers(req: &Req) -> Vec<&str> {
+        let request_scope: Vec<&Expr> = req
+            .headers
+            .iter()
+            .chain(&req.query)
+            .map(|(_, v)| v)
+            .collect();
+        let response_scope: Vec<&Expr> = req
+            .captures
+            .iter()
+            .map(|(_, v)| v)
+            .chain(req.asserts.iter().flat_map(|a| [&a.left, &a.right]))
+            .collect();
+        let contains = |exprs: &[&Expr], expr: &Expr| exprs.iter().any(|e| ptr::eq(*e, expr));
+
+        req.expressions()
+            .into_iter()
+            .flat_map(|expr| {
+                let shadowed: &[&str] = if contains(&response_scope, expr) {
+                    &RESPONSE_SCOPE
+                } else if contains(&request_scope, expr) {
+                    &REQUEST_SCOPE
+                } else {
+                    &[]
+                };
+                expr.identifiers()
+                    .into_iter()
+                    .filter(move |name| !shadowed.contains(name))
+            })
+            .collect()
+    }
+
+    /// Index statement request yang harus selesai sebelum statement `index`
+    pub fn dependencies(&self, index: usize) -> &[usize] {
+        self.deps.get(index).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
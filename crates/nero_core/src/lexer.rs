@@ -1,36 +1,113 @@
-use crate::token::Token;
+use crate::span::Span;
+use crate::token::{SpannedToken, Token};
 use std::char;
 use std::fmt;
 use std::str::FromStr;
 
-///
+/// Error yang dapat terjadi saat proses tokenisasi
 #[derive(Debug)]
 pub enum LexerError {
     /// error jika template string tidak ditutup dengan (})
-    UnclosedTemplateString,
+    UnclosedTemplateString(Span),
     /// error jika string tidak ditutup dengan (")
-    UnclosedStringLiteral,
+    UnclosedStringLiteral(Span),
     /// error jika input character yang tidak dikenali
-    UnknownCharacter(char),
+    UnknownCharacter(char, Span),
+    /// error jika angka tidak valid, misalnya terlalu besar untuk `i64`
+    InvalidNumber(String, Span),
+    /// error jika escape sequence di dalam string tidak dikenali, contoh: `\q`
+    InvalidEscape(String, Span),
+    /// error jika block comment tidak ditutup dengan (*/)
+    UnclosedComment(Span),
     // error jika statement tidak ditutup dengan titik koma (;)
     // MissingSemicolon,
 }
 
+impl LexerError {
+    /// Lokasi error di dalam source code
+    pub fn span(&self) -> Span {
+        match self {
+            LexerError::UnclosedTemplateString(span)
+            | LexerError::UnclosedStringLiteral(span)
+            | LexerError::UnknownCharacter(_, span)
+            | LexerError::InvalidNumber(_, span)
+            | LexerError::InvalidEscape(_, span)
+            | LexerError::UnclosedComment(span) => *span,
+        }
+    }
+}
+
 impl std::error::Error for LexerError {}
 
 impl fmt::Display for LexerError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            LexerError::UnknownCharacter(ch) => {
+            LexerError::UnknownCharacter(ch, _) => {
                 write!(f, "Unknown character '{}'", ch)
             }
-            LexerError::UnclosedStringLiteral => {
+            LexerError::UnclosedStringLiteral(_) => {
                 write!(f, "Unclosed string literal")
             }
-            LexerError::UnclosedTemplateString => {
+            LexerError::UnclosedTemplateString(_) => {
                 write!(f, "Unclosed template string")
             }
+            LexerError::InvalidNumber(num, _) => {
+                write!(f, "Invalid number '{}'", num)
+            }
+            LexerError::InvalidEscape(escape, _) => {
+                write!(f, "Invalid escape sequence '{}'", escape)
+            }
+            LexerError::UnclosedComment(_) => {
+                write!(f, "Unclosed block comment")
+            }
+        }
+    }
+}
+
+/// Jenis string yang dibaca oleh [`Lexer::read_string`]
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum StringKind {
+    /// `"..."`, mendukung escape dan `${}`
+    Quoted,
+    /// Teks tanpa tanda kutip seperti isi file body, hanya mendukung `${}`
+    Template,
+    /// Isi heredoc sampai index `end`, hanya mendukung `${}`.
+    /// `indent` karakter spasi dibuang dari awal setiap baris
+    Heredoc { end: usize, indent: usize },
+}
+
+/// Tabel posisi setiap karakter di dalam source code.
+///
+/// index ke-`i` berisi `(byte_offset, line, column)` dari karakter ke-`i`,
+/// ditambah satu entry di akhir untuk posisi End of File.
+struct Positions(Vec<(usize, usize, usize)>);
+
+impl Positions {
+    fn new(chars: &[char]) -> Self {
+        let mut positions = Vec::with_capacity(chars.len() + 1);
+        let (mut offset, mut line, mut column) = (0, 1, 1);
+
+        for ch in chars {
+            positions.push((offset, line, column));
+            offset += ch.len_utf8();
+            if *ch == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
         }
+        positions.push((offset, line, column));
+
+        Self(positions)
+    }
+
+    /// Membuat [`Span`] dari index karakter `start` sampai `end` (eksklusif)
+    fn span(&self, start: usize, end: usize) -> Span {
+        let last = self.0.len() - 1;
+        let (start_offset, line, column) = self.0[start.min(last)];
+        let (end_offset, _, _) = self.0[end.min(last)];
+        Span::new(start_offset, end_offset, line, column)
     }
 }
 
@@ -58,7 +135,15 @@ impl Lexer {
         let mut i = start;
         let mut ident: String = String::new();
 
-        while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '-')
+        // `-` hanya menjadi bagian identifier jika diikuti huruf, contoh:
+        // `Content-Type`, sehingga `page-1` tetap dibaca sebagai pengurangan
+        while i < chars.len()
+            && (chars[i].is_alphanumeric()
+                || chars[i] == '_'
+                || (chars[i] == '-'
+                    && chars
+                        .get(i + 1)
+                        .is_some_and(|c| c.is_alphabetic() || *c == '_')))
         {
             ident.push(chars[i]);
             i += 1;
@@ -69,126 +154,475 @@ impl Lexer {
 
     /// Membaca string literal dari source code
     ///
+    /// `start` adalah index tanda kutip pembuka untuk [`StringKind::Quoted`],
+    /// atau index karakter pertama isi string untuk jenis lainnya.
+    ///
     /// # Return
     /// Mengembalikan index setelah string_literal selesai dibaca
     fn read_string(
-        tokens: &mut Vec<Token>,
-        chars: &Vec<char>,
+        tokens: &mut Vec<SpannedToken>,
+        chars: &[char],
+        positions: &Positions,
         start: usize,
+        kind: StringKind,
     ) -> Result<usize, LexerError> {
+        let first_token = tokens.len();
         let mut part = String::new();
-        let mut i = start + 1;
+        // awal dari bagian string yang sedang dibaca, termasuk tanda kutip pembuka
+        let mut part_start = start;
+        let (mut i, end) = match kind {
+            StringKind::Quoted => (start + 1, chars.len()),
+            StringKind::Template => (start, chars.len()),
+            StringKind::Heredoc { end, .. } => (start, end),
+        };
+
+        while i < end {
+            // indentasi closing tag heredoc dibuang dari awal setiap baris
+            if let StringKind::Heredoc { indent, .. } = kind
+                && (i == start || chars[i - 1] == '\n')
+            {
+                let mut skipped = 0;
+                while skipped < indent && i < end && matches!(chars[i], ' ' | '\t') {
+                    i += 1;
+                    skipped += 1;
+                }
+                if i >= end {
+                    break;
+                }
+            }
 
-        while i < chars.len() {
             let ch = chars[i];
 
-            if ch == '$' && i + 1 < chars.len() && chars[i + 1] == '{' {
+            if ch == '\\' && kind == StringKind::Quoted {
+                let (escaped, next) = Self::read_escape(chars, positions, i)?;
+                part.push(escaped);
+                i = next;
+                continue;
+            }
+
+            if ch == '$' && i + 1 < end && chars[i + 1] == '{' {
+                let template_start = if part.is_empty() { part_start } else { i };
                 if !part.is_empty() {
-                    tokens.push(Token::StringLiteral(part.clone()));
+                    tokens.push(SpannedToken {
+                        token: Token::StringLiteral(part.clone()),
+                        span: positions.span(part_start, i),
+                    });
                     part.clear();
                 }
 
-                tokens.push(Token::TemplateStart);
+                tokens.push(SpannedToken {
+                    token: Token::TemplateStart,
+                    span: positions.span(template_start, i + 2),
+                });
                 i += 2;
 
-                let (ident, next) = Lexer::read_identifier(chars, i);
-                tokens.push(Token::Identifier(ident));
-                i = next;
-
-                if chars[i] != '}' {
-                    return Err(LexerError::UnclosedTemplateString);
-                }
+                // isi `${...}` berupa expression, contoh: `${env.API_KEY}`
+                // atau `${now("%Y-%m-%d")}`
+                let Some(close) = Self::find_template_end(chars, i, end) else {
+                    return Err(LexerError::UnclosedTemplateString(
+                        positions.span(template_start, i),
+                    ));
+                };
+                Self::tokenize_range(tokens, chars, positions, i, close)?;
+                i = close;
 
-                tokens.push(Token::TemplateEnd);
+                tokens.push(SpannedToken {
+                    token: Token::TemplateEnd,
+                    span: positions.span(i, i + 1),
+                });
                 i += 1;
+                part_start = i;
                 continue;
             }
-            if ch == '"' {
-                if !part.is_empty() {
-                    tokens.push(Token::StringLiteral(part.clone()));
+            if ch == '"' && kind == StringKind::Quoted {
+                // string kosong tetap menghasilkan satu token
+                if !part.is_empty() || tokens.len() == first_token {
+                    tokens.push(SpannedToken {
+                        token: Token::StringLiteral(part.clone()),
+                        span: positions.span(part_start, i + 1),
+                    });
                 }
                 return Ok(i + 1);
             }
             part.push(ch);
             i += 1;
         }
-        Err(LexerError::UnclosedStringLiteral)
+
+        if kind == StringKind::Quoted {
+            return Err(LexerError::UnclosedStringLiteral(
+                positions.span(start, start + 1),
+            ));
+        }
+        if !part.is_empty() || tokens.len() == first_token {
+            tokens.push(SpannedToken {
+                token: Token::StringLiteral(part),
+                span: positions.span(part_start, i),
+            });
+        }
+        Ok(i)
+    }
+
+    /// Mencari `}` penutup `${` mulai dari index `start`
+    ///
+    /// `{}` dan string di dalam expression dilewati, sehingga
+    /// `${now("}")}` dan `${ {"a": 1}.a }` tetap ditutup di tempat yang benar.
+    /// Expression tidak boleh melewati akhir baris.
+    fn find_template_end(chars: &[char], start: usize, end: usize) -> Option<usize> {
+        let mut depth = 0;
+        let mut i = start;
+
+        while i < end {
+            match chars[i] {
+                '"' => {
+                    i += 1;
+                    while i < end && !matches!(chars[i], '"' | '\n') {
+                        if chars[i] == '\\' {
+                            i += 1;
+                        }
+                        i += 1;
+                    }
+                    if chars.get(i) != Some(&'"') {
+                        return None;
+                    }
+                }
+                '\n' => return None,
+                '{' => depth += 1,
+                '}' if depth == 0 => return Some(i),
+                '}' => depth -= 1,
+                _ => {}
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// Membaca escape sequence yang diawali `\` pada index `start`
+    ///
+    /// escape yang didukung: `\"`, `\\`, `\n`, `\r`, `\t`, `\0`, `\$`
+    /// (untuk menulis `${` apa adanya) dan `\u{1F600}`.
+    ///
+    /// # Return
+    /// Mengembalikan tuple `(karakter, next_index)`
+    fn read_escape(
+        chars: &[char],
+        positions: &Positions,
+        start: usize,
+    ) -> Result<(char, usize), LexerError> {
+        let invalid = |end: usize| {
+            let end = end.min(chars.len());
+            LexerError::InvalidEscape(
+                chars[start..end].iter().collect(),
+                positions.span(start, end),
+            )
+        };
+
+        let escaped = match chars.get(start + 1) {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some('$') => '$',
+            Some('u') => {
+                if chars.get(start + 2) != Some(&'{') {
+                    return Err(invalid(start + 2));
+                }
+                let digits_start = start + 3;
+                let mut i = digits_start;
+                while i < chars.len() && chars[i].is_ascii_hexdigit() {
+                    i += 1;
+                }
+                if chars.get(i) != Some(&'}') || i == digits_start || i - digits_start > 6 {
+                    return Err(invalid(i + 1));
+                }
+
+                let hex: String = chars[digits_start..i].iter().collect();
+                let ch = u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| invalid(i + 1))?;
+                return Ok((ch, i + 1));
+            }
+            _ => return Err(invalid(start + 2)),
+        };
+
+        Ok((escaped, start + 2))
+    }
+
+    /// Membaca raw string, tanpa escape dan tanpa `${}`
+    ///
+    /// - `r"..."`, berakhir di `"` pertama
+    /// - `"""..."""`, boleh berisi `"` dan baris baru. Baris baru tepat
+    ///   setelah `"""` pembuka tidak ikut menjadi isi string.
+    ///
+    /// # Return
+    /// Mengembalikan tuple `(isi, next_index)`
+    fn read_raw_string(
+        chars: &[char],
+        positions: &Positions,
+        start: usize,
+    ) -> Result<(String, usize), LexerError> {
+        let triple = chars[start..].starts_with(&['"', '"', '"']);
+        let (mut i, delimiter): (usize, &[char]) = if triple {
+            (start + 3, &['"', '"', '"'])
+        } else {
+            (start + 2, &['"'])
+        };
+        if triple && chars.get(i) == Some(&'\n') {
+            i += 1;
+        } else if triple && chars[i..].starts_with(&['\r', '\n']) {
+            i += 2;
+        }
+
+        let content_start = i;
+        while i < chars.len() {
+            if chars[i..].starts_with(delimiter) {
+                return Ok((
+                    chars[content_start..i].iter().collect(),
+                    i + delimiter.len(),
+                ));
+            }
+            i += 1;
+        }
+
+        Err(LexerError::UnclosedStringLiteral(
+            positions.span(start, start + delimiter.len()),
+        ))
+    }
+
+    /// Membaca comment yang diawali `/` pada index `start`
+    ///
+    /// - `// ...` dilewati sampai akhir baris
+    /// - `/// ...` menjadi [`Token::DocComment`], `////` dianggap comment biasa
+    /// - `/* ... */` dilewati, boleh bersarang
+    ///
+    /// # Return
+    /// Mengembalikan index setelah comment, atau `None` jika bukan comment
+    fn read_comment(
+        tokens: &mut Vec<SpannedToken>,
+        chars: &[char],
+        positions: &Positions,
+        start: usize,
+    ) -> Result<Option<usize>, LexerError> {
+        match chars.get(start + 1) {
+            Some('/') => {
+                let end = chars[start..]
+                    .iter()
+                    .position(|c| *c == '\n')
+                    .map_or(chars.len(), |p| start + p);
+
+                let is_doc =
+                    chars.get(start + 2) == Some(&'/') && chars.get(start + 3) != Some(&'/');
+                if is_doc {
+                    let text: String = chars[start + 3..end].iter().collect();
+                    let text = text.strip_prefix(' ').unwrap_or(&text).trim_end();
+                    tokens.push(SpannedToken {
+                        token: Token::DocComment(text.to_string()),
+                        span: positions.span(start, end),
+                    });
+                }
+                Ok(Some(end))
+            }
+            Some('*') => {
+                let mut depth = 0;
+                let mut i = start;
+                while i + 1 < chars.len() {
+                    match (chars[i], chars[i + 1]) {
+                        ('/', '*') => {
+                            depth += 1;
+                            i += 2;
+                        }
+                        ('*', '/') => {
+                            depth -= 1;
+                            i += 2;
+                            if depth == 0 {
+                                return Ok(Some(i));
+                            }
+                        }
+                        _ => i += 1,
+                    }
+                }
+                Err(LexerError::UnclosedComment(
+                    positions.span(start, start + 2),
+                ))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Membaca heredoc, contoh:
+    ///
+    /// ```text
+    /// body = <<EOF
+    ///     {"name": "${name}"}
+    ///     EOF;
+    /// ```
+    ///
+    /// isi heredoc dimulai pada baris setelah `<<TAG` dan berakhir sebelum
+    /// baris yang diawali `TAG`, token setelah `TAG` dibaca seperti biasa. Indentasi baris penutup dibuang dari
+    /// setiap baris, `${}` tetap di-interpolasi, dan escape tidak diproses.
+    ///
+    /// # Return
+    /// Mengembalikan index setelah tag penutup
+    fn read_heredoc(
+        tokens: &mut Vec<SpannedToken>,
+        chars: &[char],
+        positions: &Positions,
+        start: usize,
+    ) -> Result<usize, LexerError> {
+        let (tag, mut i) = Self::read_identifier(chars, start + 2);
+        let unclosed = || {
+            LexerError::UnclosedStringLiteral(
+                positions.span(start, start + 2 + tag.chars().count()),
+            )
+        };
+
+        // sisa baris setelah `<<TAG` harus kosong
+        while i < chars.len() && matches!(chars[i], ' ' | '\t' | '\r') {
+            i += 1;
+        }
+        if chars.get(i) != Some(&'\n') {
+            return Err(unclosed());
+        }
+        let content_start = i + 1;
+
+        let mut line_start = content_start;
+        while line_start <= chars.len() {
+            let line_end = chars[line_start..]
+                .iter()
+                .position(|c| *c == '\n')
+                .map_or(chars.len(), |p| line_start + p);
+            let line = &chars[line_start..line_end];
+            let indent = line.iter().take_while(|c| matches!(c, ' ' | '\t')).count();
+            let (rest, _) = Self::read_identifier(line, indent);
+
+            if rest == tag {
+                // baris baru sebelum baris penutup tidak ikut menjadi isi
+                let end = line_start.saturating_sub(1).max(content_start);
+                Self::read_string(
+                    tokens,
+                    chars,
+                    positions,
+                    content_start,
+                    StringKind::Heredoc { end, indent },
+                )?;
+                return Ok(line_start + indent + tag.chars().count());
+            }
+            line_start = line_end + 1;
+        }
+
+        Err(unclosed())
     }
 
     /// Membaca number literal dari source code
     ///
+    /// angka yang memiliki bagian desimal (`.`) atau eksponen (`e`)
+    /// akan menjadi [`Token::FloatLiteral`], selain itu [`Token::NumberLiteral`].
+    ///
     /// # Return
-    /// Mengembalikan tuple `(number, next_index)`
-    /// - `number` adalah string hasil pembacaan
+    /// Mengembalikan tuple `(token, next_index)`
+    /// - `token` adalah token angka hasil pembacaan
     /// - `next_index` adalah posisi setelah number selesai dibaca
-    fn read_number(chars: &Vec<char>, start: usize) -> (i64, usize) {
+    fn read_number(
+        chars: &[char],
+        positions: &Positions,
+        start: usize,
+    ) -> Result<(Token, usize), LexerError> {
         let mut i = start;
         let mut num_str: String = String::new();
+        let mut is_float = false;
+
+        let read_digits = |i: &mut usize, num_str: &mut String| {
+            while *i < chars.len() && chars[*i].is_ascii_digit() {
+                num_str.push(chars[*i]);
+                *i += 1;
+            }
+        };
 
-        while i < chars.len() && chars[i].is_ascii_digit() {
-            num_str.push(chars[i]);
+        read_digits(&mut i, &mut num_str);
+
+        // bagian desimal, hanya jika `.` diikuti angka
+        if i + 1 < chars.len() && chars[i] == '.' && chars[i + 1].is_ascii_digit() {
+            is_float = true;
+            num_str.push('.');
             i += 1;
+            read_digits(&mut i, &mut num_str);
         }
 
-        let num: i64 = i64::from_str(&num_str).unwrap();
+        // bagian eksponen, contoh: `1e3`, `2.5E-4`
+        if i < chars.len() && matches!(chars[i], 'e' | 'E') {
+            let sign = i + 1 < chars.len() && matches!(chars[i + 1], '+' | '-');
+            let digit_at = if sign { i + 2 } else { i + 1 };
+            if digit_at < chars.len() && chars[digit_at].is_ascii_digit() {
+                is_float = true;
+                num_str.extend(&chars[i..digit_at]);
+                i = digit_at;
+                read_digits(&mut i, &mut num_str);
+            }
+        }
 
-        (num, i)
-    }
+        let invalid = || LexerError::InvalidNumber(num_str.clone(), positions.span(start, i));
+        let token = if is_float {
+            Token::FloatLiteral(f64::from_str(&num_str).map_err(|_| invalid())?)
+        } else {
+            Token::NumberLiteral(i64::from_str(&num_str).map_err(|_| invalid())?)
+        };
 
-    /// Fungsi untuk membantu menentukan HTTP Method dari `String`
-    ///
-    /// # Return
-    /// Mengembalikan enum [`HttpMethod`]
-    // fn read_method(method: &String) -> HttpMethod {
-    //     let m = method.to_uppercase();
-    //     if &m == "GET" {
-    //         return HttpMethod::GET;
-    //     }
-    //     if &m == "POST" {
-    //         return HttpMethod::POST;
-    //     }
-    //     if &m == "DELETE" {
-    //         return HttpMethod::DELETE;
-    //     }
-    //     if &m == "PATCH" {
-    //         return HttpMethod::PATCH;
-    //     }
-    //     if &m == "PUT" {
-    //         return HttpMethod::PUT;
-    //     }
-    //     return HttpMethod::UNKNOWN;
-    // }
+        Ok((token, i))
+    }
 
     /// Fungsi untuk melakukan tokenisasi dan pengkategorian tipe token
     ///
     /// lexer akan:
-    /// - melewati whitespace
-    /// - membaca simbol seperti `@`, `{}`, `[]`, `#`, `:`, `=`, `,`
+    /// - melewati whitespace dan comment, `///` menjadi [`Token::DocComment`]
+    /// - membaca simbol seperti `@`, `{}`, `[]`, `()`, `#`, `:`, `=`, `,`, `.`
+    /// - membaca operator `+`, `-`, `*`, `/`, `%`, `!`, `&&`, `||` dan filter `|`
+    /// - membaca operator perbandingan `==`, `!=`, `<`, `<=`, `>`, `>=`
+    /// - membaca label setelah `#[`, `[` lainnya dianggap awal dari array
     /// - membaca method setelah karakter `@`
-    /// - membaca string literal `"..."`
-    /// - membaca angka
+    /// - membaca string literal `"..."` beserta escape sequence-nya
+    /// - membaca raw string `r"..."` dan `"""..."""`, serta heredoc `<<TAG`
+    /// - membaca angka, termasuk angka desimal dan eksponen
     /// - membaca identifier
     ///
+    /// setiap token disertai [`Span`] yang menunjuk lokasinya di source code.
+    ///
     /// # Contoh
-    /// ```
+    /// ```text
     /// let source_code = "port = 3000";
-    /// let tokens: Vec<Token> = tokenize(source_code);
+    /// let tokens: Vec<SpannedToken> = tokenize(source_code);
     /// ```
     ///
     /// # Return
-    /// Vector berisi enum [`Token`] hasil lexing. Contoh:
+    /// Vector berisi [`SpannedToken`] hasil lexing. Contoh:
+    /// ```text
+    /// Identifier("port")    1:1
+    /// Equals                1:6
+    /// NumberLiteral(3000)   1:8
     /// ```
-    /// Identifier("port")
-    /// Equals
-    /// NumberLiteral("3000")
-    /// ```
-    pub fn tokenize(source_code: &str) -> Result<Vec<Token>, LexerError> {
-        let mut tokens: Vec<Token> = Vec::new();
+    pub fn tokenize(source_code: &str) -> Result<Vec<SpannedToken>, LexerError> {
+        let mut tokens: Vec<SpannedToken> = Vec::new();
 
         let chars: Vec<char> = source_code.chars().collect();
-        let mut i = 0;
+        let positions = Positions::new(&chars);
+        Self::tokenize_range(&mut tokens, &chars, &positions, 0, chars.len())?;
 
-        while i < chars.len() {
+        Ok(tokens)
+    }
+
+    /// Tokenisasi karakter dari index `start` sampai `end` (eksklusif),
+    /// dipakai untuk seluruh source code dan untuk isi `${...}`
+    fn tokenize_range(
+        tokens: &mut Vec<SpannedToken>,
+        chars: &[char],
+        positions: &Positions,
+        start: usize,
+        end: usize,
+    ) -> Result<(), LexerError> {
+        let mut i = start;
+
+        while i < end {
             let ch = chars[i];
 
             if ch.is_whitespace() {
@@ -196,84 +630,157 @@ impl Lexer {
                 continue;
             }
 
-            match ch {
-                '@' => {
-                    tokens.push(Token::At);
-                    // let (m, next) = Self::read_identifier(&chars, i + 1);
-                    // tokens.push(Token::Method(Self::read_method(&m)));
-                    i += 1;
-                    continue;
-                }
-                '#' => {
-                    tokens.push(Token::Hash);
-                    i += 1;
-                    continue;
-                }
-                '=' => {
-                    tokens.push(Token::Equals);
-                    i += 1;
-                    continue;
-                }
-                ':' => {
-                    tokens.push(Token::Colon);
-                    i += 1;
-                    continue;
-                }
-                ',' => {
-                    tokens.push(Token::Comma);
-                    i += 1;
-                    continue;
-                }
-                '{' => {
-                    tokens.push(Token::OpenBrace);
-                    i += 1;
-                    continue;
-                }
-                '}' => {
-                    tokens.push(Token::CloseBrace);
-                    i += 1;
-                    continue;
-                }
-                '[' => {
-                    tokens.push(Token::OpenBracket);
-                    let (label, next) = Self::read_identifier(&chars, i + 1);
-                    tokens.push(Token::Label(label));
-                    i = next;
-                    continue;
-                }
-                ']' => {
-                    tokens.push(Token::CloseBracket);
-                    i += 1;
-                    continue;
-                }
-                ';' => {
-                    tokens.push(Token::SemiColon);
+            if ch == '/'
+                && let Some(next) = Self::read_comment(tokens, chars, positions, i)?
+            {
+                i = next;
+                continue;
+            }
+
+            // heredoc, contoh: `<<EOF`
+            if ch == '<'
+                && chars.get(i + 1) == Some(&'<')
+                && chars
+                    .get(i + 2)
+                    .is_some_and(|c| c.is_alphabetic() || *c == '_')
+            {
+                i = Self::read_heredoc(tokens, chars, positions, i)?;
+                continue;
+            }
+
+            // operator dua karakter
+            let next = chars.get(i + 1).copied();
+            let comparison = match (ch, next) {
+                ('=', Some('=')) => Some(Token::EqualEqual),
+                ('!', Some('=')) => Some(Token::NotEqual),
+                ('<', Some('=')) => Some(Token::LessEqual),
+                ('>', Some('=')) => Some(Token::GreaterEqual),
+                ('&', Some('&')) => Some(Token::AndAnd),
+                ('|', Some('|')) => Some(Token::OrOr),
+                _ => None,
+            };
+
+            if let Some(token) = comparison {
+                tokens.push(SpannedToken {
+                    token,
+                    span: positions.span(i, i + 2),
+                });
+                i += 2;
+                continue;
+            }
+
+            let symbol = match ch {
+                '@' => Some(Token::At),
+                '#' => Some(Token::Hash),
+                '=' => Some(Token::Equals),
+                ':' => Some(Token::Colon),
+                ',' => Some(Token::Comma),
+                '-' => Some(Token::Minus),
+                '+' => Some(Token::Plus),
+                '*' => Some(Token::Star),
+                '/' => Some(Token::Slash),
+                '%' => Some(Token::Percent),
+                '!' => Some(Token::Bang),
+                '|' => Some(Token::Pipe),
+                '.' => Some(Token::Dot),
+                '<' => Some(Token::Less),
+                '>' => Some(Token::Greater),
+                '{' => Some(Token::OpenBrace),
+                '(' => Some(Token::OpenParen),
+                ')' => Some(Token::CloseParen),
+                '}' => Some(Token::CloseBrace),
+                ']' => Some(Token::CloseBracket),
+                ';' => Some(Token::SemiColon),
+                _ => None,
+            };
+
+            if let Some(token) = symbol {
+                tokens.push(SpannedToken {
+                    token,
+                    span: positions.span(i, i + 1),
+                });
+                i += 1;
+                continue;
+            }
+
+            if ch == '[' {
+                tokens.push(SpannedToken {
+                    token: Token::OpenBracket,
+                    span: positions.span(i, i + 1),
+                });
+
+                // `[` setelah `#` adalah label request, selain itu awal dari array
+                let is_label =
+                    tokens.len() >= 2 && matches!(tokens[tokens.len() - 2].token, Token::Hash);
+                if !is_label {
                     i += 1;
                     continue;
                 }
-                _ => {}
-            };
+
+                let (label, next) = Self::read_identifier(chars, i + 1);
+                tokens.push(SpannedToken {
+                    token: Token::Label(label),
+                    span: positions.span(i + 1, next),
+                });
+                i = next;
+                continue;
+            }
+
+            // raw string `r"..."` dan `"""..."""`
+            let raw = (ch == 'r' && next == Some('"')) || chars[i..].starts_with(&['"', '"', '"']);
+            if raw {
+                let (content, next) = Self::read_raw_string(chars, positions, i)?;
+                tokens.push(SpannedToken {
+                    token: Token::StringLiteral(content),
+                    span: positions.span(i, next),
+                });
+                i = next;
+                continue;
+            }
 
             if ch == '"' {
-                i = Self::read_string(&mut tokens, &chars, i)?;
+                i = Self::read_string(tokens, chars, positions, i, StringKind::Quoted)?;
                 continue;
             }
 
-            if ch.is_numeric() {
-                let (num, next) = Self::read_number(&chars, i);
-                tokens.push(Token::NumberLiteral(num));
+            if ch.is_ascii_digit() {
+                let (token, next) = Self::read_number(chars, positions, i)?;
+                tokens.push(SpannedToken {
+                    token,
+                    span: positions.span(i, next),
+                });
                 i = next;
                 continue;
             }
 
             if ch.is_alphabetic() || ch == '_' {
-                let (ident, next) = Self::read_identifier(&chars, i);
-                tokens.push(Token::Identifier(ident));
+                let (ident, next) = Self::read_identifier(chars, i);
+                let token = match ident.as_str() {
+                    "import" => Token::Import,
+                    _ => Token::Identifier(ident),
+                };
+                tokens.push(SpannedToken {
+                    token,
+                    span: positions.span(i, next),
+                });
                 i = next;
                 continue;
             }
-            return Err(LexerError::UnknownCharacter(ch));
+            return Err(LexerError::UnknownCharacter(ch, positions.span(i, i + 1)));
         }
+        Ok(())
+    }
+
+    /// Tokenisasi teks biasa yang boleh berisi `${var}`, misalnya isi file
+    /// body. Tanda kutip di dalam teks tidak memiliki arti khusus.
+    ///
+    /// hasilnya di-parse dengan [`crate::parser::Parser::parse_template`].
+    pub fn tokenize_template(text: &str) -> Result<Vec<SpannedToken>, LexerError> {
+        let mut tokens: Vec<SpannedToken> = Vec::new();
+        let chars: Vec<char> = text.chars().collect();
+        let positions = Positions::new(&chars);
+
+        Self::read_string(&mut tokens, &chars, &positions, 0, StringKind::Template)?;
         Ok(tokens)
     }
 }
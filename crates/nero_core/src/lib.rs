@@ -1,6 +1,12 @@
 pub mod ast;
+pub mod auth;
+pub mod filters;
+pub mod graph;
 pub mod lexer;
+pub mod options;
 pub mod parser;
 pub mod resolver;
 pub mod semantic;
+pub mod span;
+pub mod stdlib;
 pub mod token;
@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use crate::resolver::Value;
+
+/// Strategi jeda antar percobaan ulang
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum Backoff {
+    /// Jeda selalu sama dengan `delay`
+    #[default]
+    Fixed,
+    /// Jeda bertambah `delay` setiap percobaan
+    Linear,
+    /// Jeda berlipat dua setiap percobaan
+    Exponential,
+}
+
+impl Backoff {
+    /// Lama jeda sebelum percobaan ulang ke-`attempt` (dimulai dari 1)
+    pub fn delay(&self, base: Duration, attempt: u32) -> Duration {
+        match self {
+            Backoff::Fixed => base,
+            Backoff::Linear => base.saturating_mul(attempt),
+            Backoff::Exponential => {
+                base.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            }
+        }
+    }
+}
+
+/// Pengaturan sebuah request dari section `OPTIONS`, contoh:
+///
+/// ```text
+/// OPTIONS {
+///     timeout: 5000,
+///     follow_redirects: false,
+///     max_redirects: 3,
+///     retries: 3,
+///     retry_on: [502, 503],
+///     backoff: "exponential",
+///     delay: 200
+/// }
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct RequestOptions {
+    /// Batas waktu setiap percobaan
+    pub timeout: Option<Duration>,
+    /// Ikuti redirect secara otomatis, default `true`
+    pub follow_redirects: bool,
+    /// Jumlah maksimal redirect yang diikuti
+    pub max_redirects: Option<usize>,
+    /// Jumlah percobaan ulang setelah percobaan pertama gagal
+    pub retries: u32,
+    /// Status code yang memicu percobaan ulang, default `[502, 503, 504]`.
+    /// Request yang gagal terkirim (timeout, koneksi gagal) selalu diulang.
+    pub retry_on: Vec<u16>,
+    pub backoff: Backoff,
+    /// Jeda dasar antar percobaan, lihat [`Backoff::delay`]
+    pub delay: Duration,
+}
+
+impl Default for RequestOptions {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            follow_redirects: true,
+            max_redirects: None,
+            retries: 0,
+            retry_on: vec![502, 503, 504],
+            backoff: Backoff::Fixed,
+            delay: Duration::ZERO,
+        }
+    }
+}
+
+impl RequestOptions {
+    /// Nama option yang dikenali
+    pub const NAMES: [&str; 7] = [
+        "timeout",
+        "follow_redirects",
+        "max_redirects",
+        "retries",
+        "retry_on",
+        "backoff",
+        "delay",
+    ];
+
+    /// Mengisi option `name` dengan nilai yang sudah di-resolve
+    ///
+    /// # Return
+    /// pesan error jika nama option tidak dikenal atau tipe nilainya salah
+    pub fn set(&mut self, name: &str, value: &Value) -> Result<(), String> {
+        let millis = |value: &Value| match value {
+            Value::Number(n) if *n >= 0 => Ok(Duration::from_millis(*n as u64)),
+            _ => Err(format!("`{}` must be a number of milliseconds", name)),
+        };
+        let count = |value: &Value| match value {
+            Value::Number(n) if *n >= 0 => Ok(*n as u64),
+            _ => Err(format!("`{}` must be a positive number", name)),
+        };
+
+        match name {
+            "timeout" => self.timeout = Some(millis(value)?),
+            "delay" => self.delay = millis(value)?,
+            "follow_redirects" => match value {
+                Value::Bool(b) => self.follow_redirects = *b,
+                _ => return Err(format!("`{}` must be true or false", name)),
+            },
+            "max_redirects" => self.max_redirects = Some(count(value)? as usize),
+            "retries" => {
+                self.retries =
+                    u32::try_from(count(value)?).map_err(|_| format!("`{}` is too large", name))?
+            }
+            "retry_on" => {
+                let Value::Array(items) = value else {
+                    return Err(format!("`{}` must be an array of status codes", name));
+                };
+                self.retry_on = items
+                    .iter()
+                    .map(|item| match item {
+                        Value::Number(n) if (100..=599).contains(n) => Ok(*n as u16),
+                        _ => Err(format!("`{}` must only contain status codes", name)),
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            "backoff" => {
+                self.backoff = match value {
+                    Value::String(s) if s == "fixed" => Backoff::Fixed,
+                    Value::String(s) if s == "linear" => Backoff::Linear,
+                    Value::String(s) if s == "exponential" => Backoff::Exponential,
+                    _ => {
+                        return Err(format!(
+                            "`{}` must be \"fixed\", \"linear\" or \"exponential\"",
+                            name
+                        ));
+                    }
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "Unknown option `{}`, expected one of: {}",
+                    name,
+                    Self::NAMES.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+}
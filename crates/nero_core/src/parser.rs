@@ -1,19 +1,42 @@
+use std::collections::HashMap;
+
 use crate::{
-    ast::{Expr, Req, Stmt, StringPart},
-    token::Token,
+    ast::{
+        Assertion, BinaryOp, Body, CompareOp, Expr, ExprKind, Part, Req, Stmt, StringPart, UnaryOp,
+    },
+    span::Span,
+    token::{HttpMethod, SpannedToken, Token},
 };
 
 pub struct Parser {
-    pub tokens: Vec<Token>,
+    pub tokens: Vec<SpannedToken>,
     pub pos: usize,
+    /// Doc comment, disimpan berdasarkan index token tepat setelahnya
+    pub docs: HashMap<usize, String>,
 }
 
 #[derive(Debug)]
 pub enum ParserError {
-    InvalidExpression,
-    UnexpectedEOF,
-    MissingTitle,
-    UnexpectedToken { expected: Token, found: Token },
+    InvalidExpression(Span),
+    UnexpectedEOF(Span),
+    MissingTitle(Span),
+    UnexpectedToken {
+        expected: Token,
+        found: Token,
+        span: Span,
+    },
+}
+
+impl ParserError {
+    /// Lokasi error di dalam source code
+    pub fn span(&self) -> Span {
+        match self {
+            ParserError::InvalidExpression(span)
+            | ParserError::UnexpectedEOF(span)
+            | ParserError::MissingTitle(span)
+            | ParserError::UnexpectedToken { span, .. } => *span,
+        }
+    }
 }
 
 impl std::error::Error for ParserError {}
@@ -21,16 +44,18 @@ impl std::error::Error for ParserError {}
 impl std::fmt::Display for ParserError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ParserError::MissingTitle => {
+            ParserError::MissingTitle(_) => {
                 write!(f, "Missing title")
             }
-            ParserError::InvalidExpression => {
+            ParserError::InvalidExpression(_) => {
                 write!(f, "Invalid expression")
             }
-            ParserError::UnexpectedEOF => {
+            ParserError::UnexpectedEOF(_) => {
                 write!(f, "Unexpected Enf of File")
             }
-            ParserError::UnexpectedToken { expected, found } => {
+            ParserError::UnexpectedToken {
+                expected, found, ..
+            } => {
                 write!(f, "Expected: {:?}, found: {:?}", expected, found)
             }
         }
@@ -38,13 +63,82 @@ impl std::fmt::Display for ParserError {
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, pos: 0 }
+    /// Membuat parser dari hasil lexer
+    ///
+    /// [`Token::DocComment`] dipisahkan dari token lain, lalu baris-baris
+    /// yang berurutan digabung dan dipasang ke request setelahnya.
+    pub fn new(tokens: Vec<SpannedToken>) -> Self {
+        let mut docs = HashMap::new();
+        let mut doc: Vec<String> = Vec::new();
+        let mut filtered = Vec::with_capacity(tokens.len());
+
+        for token in tokens {
+            match token.token {
+                Token::DocComment(text) => doc.push(text),
+                _ => {
+                    if !doc.is_empty() {
+                        docs.insert(filtered.len(), doc.join("\n"));
+                        doc.clear();
+                    }
+                    filtered.push(token);
+                }
+            }
+        }
+
+        Self {
+            tokens: filtered,
+            pos: 0,
+            docs,
+        }
     }
 
     /// Helper untuk mengambil token saat ini
     fn current(&self) -> Option<&Token> {
-        self.tokens.get(self.pos)
+        self.tokens.get(self.pos).map(|t| &t.token)
+    }
+
+    /// Helper untuk mengambil lokasi token saat ini
+    ///
+    /// jika parser sudah mencapai akhir token, maka akan dikembalikan
+    /// posisi tepat setelah token terakhir.
+    fn current_span(&self) -> Span {
+        match self.tokens.get(self.pos) {
+            Some(t) => t.span,
+            None => self.eof_span(),
+        }
+    }
+
+    /// Helper untuk mengambil lokasi token sebelumnya
+    fn previous_span(&self) -> Span {
+        match self.pos.checked_sub(1).and_then(|p| self.tokens.get(p)) {
+            Some(t) => t.span,
+            None => self.current_span(),
+        }
+    }
+
+    /// Lokasi End of File, yaitu tepat setelah token terakhir
+    fn eof_span(&self) -> Span {
+        match self.tokens.last() {
+            Some(t) => Span::new(
+                t.span.end,
+                t.span.end,
+                t.span.line,
+                t.span.column + (t.span.end - t.span.start),
+            ),
+            None => Span::new(0, 0, 1, 1),
+        }
+    }
+
+    /// Helper untuk membuat error dari token saat ini
+    fn unexpected(&self, expected: Token) -> ParserError {
+        match self.tokens.get(self.pos) {
+            Some(t) => ParserError::UnexpectedToken {
+                expected,
+                found: t.token.clone(),
+                span: t.span,
+            },
+            None => ParserError::UnexpectedEOF(self.eof_span()),
+        }
     }
 
     /// Helper untuk memindahkan parser ke token berikutnya
@@ -52,38 +146,46 @@ impl Parser {
         self.pos += 1;
     }
 
-    /// Helper untuk
+    /// Helper untuk memastikan token saat ini sesuai dengan `expected`,
+    /// lalu memindahkan parser ke token berikutnya
     fn consume(&mut self, expected: &Token) -> Result<(), ParserError> {
-        if let Some(t) = self.current() {
-            if t == expected {
-                self.advance();
-                Ok(())
-            } else {
-                Err(ParserError::UnexpectedToken {
-                    expected: expected.clone(),
-                    found: t.clone(),
-                })
-            }
+        if self.current() == Some(expected) {
+            self.advance();
+            Ok(())
         } else {
-            Err(ParserError::UnexpectedEOF)
+            Err(self.unexpected(expected.clone()))
         }
     }
 
     /// Fungsi untuk membantu parse sebuah statement
     fn parse_statement(&mut self) -> Result<Stmt, ParserError> {
+        let next = self.tokens.get(self.pos + 1).map(|t| &t.token);
         match self.current() {
+            Some(Token::Identifier(name))
+                if name == "env" && matches!(next, Some(Token::Identifier(_))) =>
+            {
+                self.parse_env()
+            }
             Some(Token::Identifier(_)) => self.parse_assignment(),
+            Some(Token::Import) => self.parse_import(),
             Some(Token::Hash) | Some(Token::At) => self.parse_request(),
-            Some(t) => Err(ParserError::UnexpectedToken {
-                expected: Token::Identifier("statement".into()),
-                found: t.clone(),
-            }),
-            None => Err(ParserError::UnexpectedEOF),
+            _ => Err(self.unexpected(Token::Identifier("statement".into()))),
+        }
+    }
+
+    /// Fungsi untuk parse hasil [`crate::lexer::Lexer::tokenize_template`]
+    /// menjadi satu expression string
+    pub fn parse_template(&mut self) -> Result<Expr, ParserError> {
+        let expr = self.parse_interpolated_string()?;
+        match self.current() {
+            None => Ok(expr),
+            Some(_) => Err(ParserError::InvalidExpression(self.current_span())),
         }
     }
 
     fn parse_interpolated_string(&mut self) -> Result<Expr, ParserError> {
         let mut parts: Vec<StringPart> = Vec::new();
+        let start = self.current_span();
 
         loop {
             match self.current() {
@@ -94,13 +196,12 @@ impl Parser {
                 Some(Token::TemplateStart) => {
                     self.advance();
 
+                    // `${}` kosong tidak diperbolehkan
                     let expr = match self.current() {
-                        Some(Token::Identifier(name)) => {
-                            let e = Expr::Identifier(name.clone());
-                            self.advance();
-                            e
+                        Some(Token::TemplateEnd) | None => {
+                            return Err(ParserError::InvalidExpression(self.current_span()));
                         }
-                        _ => return Err(ParserError::InvalidExpression),
+                        Some(_) => self.parse_expression()?,
                     };
                     self.consume(&Token::TemplateEnd)?;
                     parts.push(StringPart::Expression(expr));
@@ -108,29 +209,281 @@ impl Parser {
                 _ => break,
             }
         }
-        Ok(Expr::String(parts))
+        Ok(Expr::new(
+            ExprKind::String(parts),
+            start.to(self.previous_span()),
+        ))
+    }
+
+    /// Fungsi untuk parse array
+    ///
+    /// # Grammar
+    /// `array = "[" (expr ("," expr)* ","?)? "]"`
+    ///
+    fn parse_array(&mut self) -> Result<Expr, ParserError> {
+        let start = self.current_span();
+        let mut items: Vec<Expr> = Vec::new();
+        self.consume(&Token::OpenBracket)?;
+
+        while !matches!(self.current(), Some(Token::CloseBracket)) {
+            items.push(self.parse_expression()?);
+
+            if matches!(self.current(), Some(Token::Comma)) {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+
+        self.consume(&Token::CloseBracket)?;
+        Ok(Expr::new(
+            ExprKind::Array(items),
+            start.to(self.previous_span()),
+        ))
     }
 
+    /// Fungsi untuk parse expression lengkap beserta operator dan filternya
+    ///
+    /// # Grammar
+    /// `expr = binary ("|" Identifier ("(" (expr ("," expr)*)? ")")?)*`
+    /// `binary = unary (binary_op unary)*`
+    ///
+    /// operator dengan prioritas lebih tinggi dievaluasi lebih dulu, lihat
+    /// [`BinaryOp::precedence`]. Operator dengan prioritas sama dibaca dari kiri.
+    /// Filter dievaluasi paling akhir, sehingga `a + b | upper` sama dengan
+    /// `(a + b) | upper`.
     fn parse_expression(&mut self) -> Result<Expr, ParserError> {
+        let mut expr = self.parse_binary(0)?;
+
+        while matches!(self.current(), Some(Token::Pipe)) {
+            self.advance();
+            let (name, args) = match self.tokens.get(self.pos + 1).map(|t| &t.token) {
+                Some(Token::OpenParen) => match self.parse_call()?.kind {
+                    ExprKind::Call(name, args) => (name, args),
+                    _ => unreachable!("parse_call always returns ExprKind::Call"),
+                },
+                _ => match self.current() {
+                    Some(Token::Identifier(name)) => {
+                        let name = name.clone();
+                        self.advance();
+                        (name, Vec::new())
+                    }
+                    _ => return Err(self.unexpected(Token::Identifier("filter".into()))),
+                },
+            };
+
+            let span = expr.span.to(self.previous_span());
+            expr = Expr::new(ExprKind::Filter(Box::new(expr), name, args), span);
+        }
+
+        Ok(expr)
+    }
+
+    /// Precedence climbing, hanya membaca operator dengan prioritas minimal
+    /// `min_precedence`
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Expr, ParserError> {
+        let mut left = self.parse_unary()?;
+
+        while let Some(op) = self.current().and_then(Self::binary_op) {
+            let precedence = op.precedence();
+            if precedence < min_precedence {
+                break;
+            }
+            self.advance();
+
+            let right = self.parse_binary(precedence + 1)?;
+            let span = left.span.to(right.span);
+            left = Expr::new(ExprKind::Binary(Box::new(left), op, Box::new(right)), span);
+        }
+
+        Ok(left)
+    }
+
+    fn binary_op(token: &Token) -> Option<BinaryOp> {
+        match token {
+            Token::Plus => Some(BinaryOp::Add),
+            Token::Minus => Some(BinaryOp::Sub),
+            Token::Star => Some(BinaryOp::Mul),
+            Token::Slash => Some(BinaryOp::Div),
+            Token::Percent => Some(BinaryOp::Rem),
+            Token::EqualEqual => Some(BinaryOp::Compare(CompareOp::Eq)),
+            Token::NotEqual => Some(BinaryOp::Compare(CompareOp::NotEq)),
+            Token::Less => Some(BinaryOp::Compare(CompareOp::Lt)),
+            Token::LessEqual => Some(BinaryOp::Compare(CompareOp::LtEq)),
+            Token::Greater => Some(BinaryOp::Compare(CompareOp::Gt)),
+            Token::GreaterEqual => Some(BinaryOp::Compare(CompareOp::GtEq)),
+            Token::AndAnd => Some(BinaryOp::And),
+            Token::OrOr => Some(BinaryOp::Or),
+            _ => None,
+        }
+    }
+
+    /// Fungsi untuk parse operator unary
+    ///
+    /// # Grammar
+    /// `unary = ("!" | "-") unary | postfix`
+    ///
+    /// `-` yang diikuti angka langsung menjadi literal angka negatif.
+    fn parse_unary(&mut self) -> Result<Expr, ParserError> {
+        let span = self.current_span();
+        let op = match self.current() {
+            Some(Token::Bang) => UnaryOp::Not,
+            Some(Token::Minus) => {
+                let kind = match self.tokens.get(self.pos + 1).map(|t| &t.token) {
+                    Some(Token::NumberLiteral(n)) => Some(ExprKind::Number(-*n)),
+                    Some(Token::FloatLiteral(n)) => Some(ExprKind::Float(-*n)),
+                    _ => None,
+                };
+                if let Some(kind) = kind {
+                    self.advance();
+                    self.advance();
+                    return self.parse_postfix(Expr::new(kind, span.to(self.previous_span())));
+                }
+                UnaryOp::Neg
+            }
+            _ => {
+                let primary = self.parse_primary()?;
+                return self.parse_postfix(primary);
+            }
+        };
+        self.advance();
+
+        let operand = self.parse_unary()?;
+        let span = span.to(operand.span);
+        Ok(Expr::new(ExprKind::Unary(op, Box::new(operand)), span))
+    }
+
+    /// Fungsi untuk parse akses field dan index setelah sebuah expression
+    ///
+    /// # Grammar
+    /// `postfix = primary ("." (Identifier | String) | "[" expr "]")*`
+    ///
+    fn parse_postfix(&mut self, mut expr: Expr) -> Result<Expr, ParserError> {
+        loop {
+            match self.current() {
+                Some(Token::Dot) => {
+                    self.advance();
+                    let field = match self.current() {
+                        Some(Token::Identifier(name)) | Some(Token::StringLiteral(name)) => {
+                            name.clone()
+                        }
+                        _ => return Err(ParserError::InvalidExpression(self.current_span())),
+                    };
+                    self.advance();
+                    let span = expr.span.to(self.previous_span());
+                    expr = Expr::new(ExprKind::Member(Box::new(expr), field), span);
+                }
+                Some(Token::OpenBracket) => {
+                    self.advance();
+                    let index = self.parse_expression()?;
+                    self.consume(&Token::CloseBracket)?;
+                    let span = expr.span.to(self.previous_span());
+                    expr = Expr::new(ExprKind::Index(Box::new(expr), Box::new(index)), span);
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    /// Fungsi untuk parse pemanggilan fungsi
+    ///
+    /// # Grammar
+    /// `call = Identifier "(" (expr ("," expr)*)? ")"`
+    ///
+    fn parse_call(&mut self) -> Result<Expr, ParserError> {
+        let start = self.current_span();
+        let name = if let Some(Token::Identifier(name)) = self.current() {
+            name.clone()
+        } else {
+            return Err(ParserError::InvalidExpression(start));
+        };
+        self.advance();
+        self.consume(&Token::OpenParen)?;
+
+        let mut args: Vec<Expr> = Vec::new();
+        while !matches!(self.current(), Some(Token::CloseParen)) {
+            args.push(self.parse_expression()?);
+
+            if matches!(self.current(), Some(Token::Comma)) {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+
+        self.consume(&Token::CloseParen)?;
+        Ok(Expr::new(
+            ExprKind::Call(name, args),
+            start.to(self.previous_span()),
+        ))
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ParserError> {
+        let span = self.current_span();
         match self.current() {
             // number literal
             Some(Token::NumberLiteral(n)) => {
                 let val = *n;
                 self.advance();
-                Ok(Expr::Number(val))
+                Ok(Expr::new(ExprKind::Number(val), span))
+            }
+            Some(Token::FloatLiteral(n)) => {
+                let val = *n;
+                self.advance();
+                Ok(Expr::new(ExprKind::Float(val), span))
+            }
+
+            // expression di dalam kurung, contoh: `(page - 1) * size`
+            Some(Token::OpenParen) => {
+                self.advance();
+                let mut expr = self.parse_expression()?;
+                self.consume(&Token::CloseParen)?;
+                expr.span = span.to(self.previous_span());
+                Ok(expr)
+            }
+
+            // keyword literal
+            Some(Token::Identifier(name)) if name == "true" || name == "false" => {
+                let val = name == "true";
+                self.advance();
+                Ok(Expr::new(ExprKind::Bool(val), span))
+            }
+            Some(Token::Identifier(name)) if name == "null" => {
+                self.advance();
+                Ok(Expr::new(ExprKind::Null, span))
+            }
+
+            // pemanggilan fungsi
+            Some(Token::Identifier(_))
+                if matches!(
+                    self.tokens.get(self.pos + 1).map(|t| &t.token),
+                    Some(Token::OpenParen)
+                ) =>
+            {
+                self.parse_call()
             }
 
             // identifier
             Some(Token::Identifier(name)) => {
                 let id = name.clone();
                 self.advance();
-                Ok(Expr::Identifier(id))
+                Ok(Expr::new(ExprKind::Identifier(id), span))
             }
             // string template (interpolated string)
             Some(Token::StringLiteral(_)) | Some(Token::TemplateStart) => {
                 self.parse_interpolated_string()
             }
-            _ => Err(ParserError::InvalidExpression),
+
+            // object & array
+            Some(Token::OpenBrace) => {
+                let items = self.parse_kv_block()?;
+                Ok(Expr::new(
+                    ExprKind::Object(items),
+                    span.to(self.previous_span()),
+                ))
+            }
+            Some(Token::OpenBracket) => self.parse_array(),
+            _ => Err(ParserError::InvalidExpression(span)),
         }
     }
 
@@ -140,25 +493,77 @@ impl Parser {
     /// `assignment = Identifier "=" expr ";"`
     ///
     fn parse_assignment(&mut self) -> Result<Stmt, ParserError> {
+        let start = self.current_span();
         let name = if let Some(Token::Identifier(name)) = self.current() {
             name.clone()
         } else {
-            return Err(ParserError::InvalidExpression);
+            return Err(ParserError::InvalidExpression(start));
         };
         self.advance();
         self.consume(&Token::Equals)?;
         let value = self.parse_expression()?;
         self.consume(&Token::SemiColon)?;
 
-        Ok(Stmt::Assignment { name, value })
+        Ok(Stmt::Assignment {
+            name,
+            value,
+            span: start.to(self.previous_span()),
+        })
+    }
+
+    /// Fungsi untuk parse block environment
+    ///
+    /// # Grammar
+    /// `env = "env" Identifier assignment_block`
+    ///
+    fn parse_env(&mut self) -> Result<Stmt, ParserError> {
+        let start = self.current_span();
+        self.advance();
+
+        let name = if let Some(Token::Identifier(name)) = self.current() {
+            name.clone()
+        } else {
+            return Err(ParserError::InvalidExpression(self.current_span()));
+        };
+        self.advance();
+
+        let assignments = self.parse_assignment_block()?;
+
+        Ok(Stmt::Env {
+            name,
+            assignments,
+            span: start.to(self.previous_span()),
+        })
+    }
+
+    /// Fungsi untuk parse statement import
+    ///
+    /// # Grammar
+    /// `import = "import" String ";"`
+    ///
+    fn parse_import(&mut self) -> Result<Stmt, ParserError> {
+        let start = self.current_span();
+        self.advance();
+
+        let path = match self.current() {
+            Some(Token::StringLiteral(path)) => path.clone(),
+            _ => return Err(self.unexpected(Token::StringLiteral("path".into()))),
+        };
+        self.advance();
+        self.consume(&Token::SemiColon)?;
+
+        Ok(Stmt::Import {
+            path,
+            span: start.to(self.previous_span()),
+        })
     }
 
-    /// Helper buat parse key value
+    /// Helper buat parse key value, key boleh berupa string atau identifier
     fn parse_kv_pair(&mut self) -> Result<(String, Expr), ParserError> {
-        let key = if let Some(Token::StringLiteral(k)) = self.current() {
+        let key = if let Some(Token::StringLiteral(k) | Token::Identifier(k)) = self.current() {
             k.clone()
         } else {
-            return Err(ParserError::InvalidExpression);
+            return Err(ParserError::InvalidExpression(self.current_span()));
         };
         self.advance();
         self.consume(&Token::Colon)?;
@@ -183,12 +588,170 @@ impl Parser {
         Ok(items)
     }
 
+    /// Fungsi untuk parse block berisi assignment, dipakai oleh `CAPTURE` dan `env`
+    ///
+    /// # Grammar
+    /// `assignment_block = "{" assignment* "}"`
+    ///
+    fn parse_assignment_block(&mut self) -> Result<Vec<(String, Expr)>, ParserError> {
+        let mut items: Vec<(String, Expr)> = Vec::new();
+        self.consume(&Token::OpenBrace)?;
+
+        while !matches!(self.current(), Some(Token::CloseBrace)) {
+            if let Stmt::Assignment { name, value, .. } = self.parse_assignment()? {
+                items.push((name, value));
+            }
+        }
+
+        self.consume(&Token::CloseBrace)?;
+        Ok(items)
+    }
+
+    /// Fungsi untuk parse satu assertion
+    ///
+    /// # Grammar
+    /// `assertion = operand ("==" | "!=" | "<" | "<=" | ">" | ">=" | "contains") operand`
+    ///
+    ///
+    /// kedua ruas boleh berisi operator aritmatika, contoh: `body.total > page * 10`.
+    fn parse_assertion(&mut self) -> Result<Assertion, ParserError> {
+        let operand = BinaryOp::Add.precedence();
+        let left = self.parse_binary(operand)?;
+        let op = match self.current() {
+            Some(Token::EqualEqual) => CompareOp::Eq,
+            Some(Token::NotEqual) => CompareOp::NotEq,
+            Some(Token::Less) => CompareOp::Lt,
+            Some(Token::LessEqual) => CompareOp::LtEq,
+            Some(Token::Greater) => CompareOp::Gt,
+            Some(Token::GreaterEqual) => CompareOp::GtEq,
+            Some(Token::Identifier(name)) if name == "contains" => CompareOp::Contains,
+            _ => return Err(self.unexpected(Token::Identifier("operator".into()))),
+        };
+        self.advance();
+        let right = self.parse_binary(operand)?;
+
+        Ok(Assertion {
+            span: left.span.to(right.span),
+            left,
+            op,
+            right,
+        })
+    }
+
+    /// Fungsi untuk parse block assertion
+    ///
+    /// # Grammar
+    /// `assert_block = "{" (assertion ";"?)* "}"`
+    ///
+    fn parse_assert_block(&mut self) -> Result<Vec<Assertion>, ParserError> {
+        let mut items: Vec<Assertion> = Vec::new();
+        self.consume(&Token::OpenBrace)?;
+
+        while !matches!(self.current(), Some(Token::CloseBrace)) {
+            items.push(self.parse_assertion()?);
+
+            if matches!(self.current(), Some(Token::SemiColon)) {
+                self.advance();
+            }
+        }
+
+        self.consume(&Token::CloseBrace)?;
+        Ok(items)
+    }
+
+    /// Fungsi untuk parse isi section `BODY`
+    ///
+    /// # Grammar
+    /// `body = ("json"? kv_block) | ("form" kv_block) | ("multipart" kv_block) | ("raw" expr) | file_call`
+    /// `file_call = "file" "(" expr ("," "{" "interpolate" ":" bool "}")? ")"`
+    ///
+    /// nilai `file(path)` di dalam body multipart menjadi [`Part::File`].
+    fn parse_body(&mut self) -> Result<Body, ParserError> {
+        if matches!(self.current(), Some(Token::Identifier(name)) if name == "file") {
+            return self.parse_file_body();
+        }
+
+        let kind = match self.current() {
+            Some(Token::Identifier(kind)) => {
+                let kind = kind.to_lowercase();
+                self.advance();
+                kind
+            }
+            _ => "json".into(),
+        };
+
+        match kind.as_str() {
+            "json" => {
+                let start = self.current_span();
+                let body = self.parse_kv_block()?;
+                Ok(Body::Json(Expr::new(
+                    ExprKind::Object(body),
+                    start.to(self.previous_span()),
+                )))
+            }
+            "raw" => Ok(Body::Raw(self.parse_expression()?)),
+            "form" => Ok(Body::Form(self.parse_kv_block()?)),
+            "multipart" => {
+                let parts = self
+                    .parse_kv_block()?
+                    .into_iter()
+                    .map(|(name, expr)| match expr.kind {
+                        ExprKind::Call(function, mut args) if function == "file" => {
+                            match (args.pop(), args.is_empty()) {
+                                (Some(path), true) => Ok((name, Part::File(path))),
+                                _ => Err(ParserError::InvalidExpression(expr.span)),
+                            }
+                        }
+                        _ => Ok((name, Part::Text(expr))),
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(Body::Multipart(parts))
+            }
+            _ => Err(ParserError::UnexpectedToken {
+                expected: Token::Identifier("json|form|multipart|raw".into()),
+                found: Token::Identifier(kind),
+                span: self.previous_span(),
+            }),
+        }
+    }
+
+    /// Fungsi untuk parse `BODY file("path")` dan
+    /// `BODY file("path", { interpolate: true })`
+    fn parse_file_body(&mut self) -> Result<Body, ParserError> {
+        let expr = self.parse_expression()?;
+        let ExprKind::Call(_, mut args) = expr.kind else {
+            return Err(ParserError::InvalidExpression(expr.span));
+        };
+
+        let interpolate = match args.len() {
+            1 => false,
+            2 => match args.pop().map(|e| e.kind) {
+                Some(ExprKind::Object(fields)) => match fields.as_slice() {
+                    [] => false,
+                    [(name, value)] if name == "interpolate" => match value.kind {
+                        ExprKind::Bool(b) => b,
+                        _ => return Err(ParserError::InvalidExpression(value.span)),
+                    },
+                    _ => return Err(ParserError::InvalidExpression(expr.span)),
+                },
+                _ => return Err(ParserError::InvalidExpression(expr.span)),
+            },
+            _ => return Err(ParserError::InvalidExpression(expr.span)),
+        };
+
+        Ok(Body::File {
+            path: args.remove(0),
+            interpolate,
+        })
+    }
+
     /// parse block section
     fn parse_section(&mut self, req: &mut Req) -> Result<(), ParserError> {
+        let section_span = self.current_span();
         let section_name = if let Some(Token::Identifier(name)) = self.current() {
             name.clone().to_uppercase()
         } else {
-            return Err(ParserError::InvalidExpression);
+            return Err(ParserError::InvalidExpression(section_span));
         };
         self.advance();
 
@@ -201,14 +764,39 @@ impl Parser {
                 let query = self.parse_kv_block()?;
                 req.query = query;
             }
+            "COOKIES" => {
+                req.cookies = self.parse_kv_block()?;
+            }
             "BODY" => {
-                let body = self.parse_kv_block()?;
-                req.body = Some(body);
+                req.body = Some(self.parse_body()?);
+            }
+            "CAPTURE" => {
+                req.captures = self.parse_assignment_block()?;
+            }
+            "ASSERT" => {
+                req.asserts = self.parse_assert_block()?;
+            }
+            "OPTIONS" => {
+                req.options = self.parse_kv_block()?;
+            }
+            "AUTH" => {
+                let start = self.current_span();
+                let mut schemes = self.parse_kv_block()?;
+                // hanya boleh ada satu skema
+                if schemes.len() != 1 {
+                    return Err(ParserError::InvalidExpression(
+                        start.to(self.previous_span()),
+                    ));
+                }
+                req.auth = schemes.pop();
             }
             _ => {
                 return Err(ParserError::UnexpectedToken {
-                    expected: Token::Identifier("HEADERS|QUERY|BODY".into()),
+                    expected: Token::Identifier(
+                        "HEADERS|QUERY|COOKIES|BODY|CAPTURE|ASSERT|OPTIONS|AUTH".into(),
+                    ),
                     found: Token::Identifier(section_name),
+                    span: section_span,
                 });
             }
         }
@@ -222,6 +810,8 @@ impl Parser {
     /// request = label? "@" Identifier expr block
     ///
     fn parse_request(&mut self) -> Result<Stmt, ParserError> {
+        let start = self.current_span();
+        let doc = self.docs.get(&self.pos).cloned();
         let label: String = if matches!(self.current(), Some(Token::Hash)) {
             self.advance();
             self.consume(&Token::OpenBracket)?;
@@ -229,20 +819,20 @@ impl Parser {
             let name = if let Some(Token::Label(name)) = self.current() {
                 name.clone()
             } else {
-                return Err(ParserError::InvalidExpression);
+                return Err(ParserError::InvalidExpression(self.current_span()));
             };
             self.advance();
             self.consume(&Token::CloseBracket)?;
             name
         } else {
-            return Err(ParserError::MissingTitle);
+            return Err(ParserError::MissingTitle(start));
         };
 
         self.consume(&Token::At)?;
         let method = if let Some(Token::Identifier(m)) = self.current() {
-            m.clone()
+            HttpMethod::from(m.as_str())
         } else {
-            return Err(ParserError::InvalidExpression);
+            return Err(ParserError::InvalidExpression(self.current_span()));
         };
 
         self.advance();
@@ -255,6 +845,13 @@ impl Parser {
             headers: vec![],
             query: vec![],
             body: None,
+            captures: vec![],
+            asserts: vec![],
+            options: vec![],
+            auth: None,
+            cookies: vec![],
+            doc,
+            span: start,
         };
 
         self.consume(&Token::OpenBrace)?;
@@ -262,18 +859,102 @@ impl Parser {
             self.parse_section(&mut req)?;
         }
         self.consume(&Token::CloseBrace)?;
+        req.span = start.to(self.previous_span());
 
-        Ok(Stmt::Request(req))
+        Ok(Stmt::Request(Box::new(req)))
     }
 
-    /// Entry point fungsi parser
-    pub fn parse(&mut self) -> Result<Vec<Stmt>, ParserError> {
+    /// Melewati token sampai batas statement berikutnya setelah terjadi error
+    ///
+    /// batas statement yang dikenali:
+    /// - `;` penutup assignment di luar request (ikut dilewati)
+    /// - `#` awal dari label request berikutnya (tidak dilewati)
+    /// - `@` awal dari request tanpa label, di luar `{}` (tidak dilewati)
+    /// - `}` penutup request (ikut dilewati), kecuali jika statement yang
+    ///   gagal adalah assignment: `}` di sana milik object, misalnya
+    ///   `x = { a: @ };`, jadi parser melompat sampai `;`
+    ///
+    /// `start` adalah posisi awal statement yang gagal di-parse, dipakai untuk
+    /// menghitung kedalaman `{}` saat error terjadi.
+    fn synchronize(&mut self, start: usize) {
+        let token_at = |i: usize| self.tokens.get(i).map(|t| &t.token);
+        let assignment = matches!(
+            (token_at(start), token_at(start + 1)),
+            (Some(Token::Identifier(_)), Some(Token::Equals))
+        );
+        if self.pos == start {
+            self.advance();
+        }
+
+        let mut depth: usize = self.tokens[start..self.pos.min(self.tokens.len())]
+            .iter()
+            .fold(0, |depth, t| match t.token {
+                Token::OpenBrace => depth + 1,
+                Token::CloseBrace => depth.saturating_sub(1),
+                _ => depth,
+            });
+
+        while let Some(token) = self.current() {
+            match token {
+                Token::SemiColon if depth == 0 => {
+                    self.advance();
+                    return;
+                }
+                Token::Hash | Token::Import => return,
+                Token::At if depth == 0 => return,
+                Token::OpenBrace => depth += 1,
+                Token::CloseBrace if assignment => depth = depth.saturating_sub(1),
+                Token::CloseBrace => {
+                    self.advance();
+                    if depth <= 1 {
+                        return;
+                    }
+                    depth -= 1;
+                    continue;
+                }
+                _ => {}
+            }
+            self.advance();
+        }
+    }
+
+    /// Parse seluruh token tanpa berhenti di error pertama
+    ///
+    /// setiap kali sebuah statement gagal di-parse, parser akan melompat ke
+    /// batas statement berikutnya (lihat [`Parser::synchronize`]) lalu
+    /// melanjutkan parsing.
+    ///
+    /// # Return
+    /// Tuple `(statements, errors)` berisi semua statement yang berhasil
+    /// di-parse beserta semua error yang ditemukan.
+    pub fn parse_all(&mut self) -> (Vec<Stmt>, Vec<ParserError>) {
         let mut statements: Vec<Stmt> = Vec::new();
+        let mut errors: Vec<ParserError> = Vec::new();
 
         while self.pos < self.tokens.len() {
-            statements.push(self.parse_statement()?);
+            let start = self.pos;
+            match self.parse_statement() {
+                Ok(stmt) => statements.push(stmt),
+                Err(err) => {
+                    errors.push(err);
+                    self.synchronize(start);
+                }
+            }
         }
 
-        Ok(statements)
+        (statements, errors)
+    }
+
+    /// Entry point fungsi parser
+    ///
+    /// mengembalikan error pertama yang ditemukan, gunakan [`Parser::parse_all`]
+    /// untuk mendapatkan semua error sekaligus.
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, ParserError> {
+        let (statements, errors) = self.parse_all();
+
+        match errors.into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(statements),
+        }
     }
 }
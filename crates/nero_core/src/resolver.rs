@@ -1,29 +1,149 @@
 use std::collections::HashMap;
 
-use crate::ast::{Expr, Stmt, StringPart};
+use crate::{
+    ast::{Assertion, BinaryOp, CompareOp, Expr, ExprKind, Stmt, StringPart, UnaryOp},
+    filters::Filters,
+    span::Span,
+    stdlib::{Function, ParamType, Stdlib},
+};
 
+#[derive(Clone)]
 pub struct Resolver {
     pub variables: HashMap<String, Value>,
+    /// Variabel yang tidak bisa ditimpa oleh script, contoh: dari `--var key=value`
+    pub overrides: HashMap<String, Value>,
+    /// Environment variable OS yang bisa diakses lewat `env.NAME` atau `env("NAME")`
+    pub env_vars: HashMap<String, String>,
+    /// Fungsi yang bisa dipanggil dari expression
+    pub stdlib: Stdlib,
+    /// Filter yang bisa dipakai setelah `|`
+    pub filters: Filters,
 }
 
-#[derive(Debug, Clone)]
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Number(i64),
+    Float(f64),
+    Bool(bool),
+    Null,
     String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Mengubah value menjadi [`serde_json::Value`] dengan tetap
+    /// mempertahankan tipe datanya
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::Number(n) => serde_json::Value::from(*n),
+            Value::Float(n) => serde_json::Number::from_f64(*n)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+            Value::Bool(b) => serde_json::Value::Bool(*b),
+            Value::Null => serde_json::Value::Null,
+            Value::String(s) => serde_json::Value::String(s.clone()),
+            Value::Array(items) => items.iter().map(Value::to_json).collect(),
+            Value::Object(items) => serde_json::Value::Object(
+                items
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.to_json()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<serde_json::Value> for Value {
+    fn from(json: serde_json::Value) -> Self {
+        match json {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Bool(b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(n) => Value::Number(n),
+                None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            serde_json::Value::String(s) => Value::String(s),
+            serde_json::Value::Array(items) => {
+                Value::Array(items.into_iter().map(Value::from).collect())
+            }
+            serde_json::Value::Object(items) => Value::Object(
+                items
+                    .into_iter()
+                    .map(|(k, v)| (k, Value::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Representasi value saat dipakai di dalam string template.
+///
+/// string ditulis apa adanya, sedangkan array dan object ditulis sebagai JSON.
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Null => write!(f, "null"),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Array(_) | Value::Object(_) => write!(f, "{}", self.to_json()),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum ResolverError {
-    UndefinedVariable(String),
-    InvalidExpression,
+    UndefinedVariable(String, Span),
+    UndefinedField(String, Span),
+    UndefinedEnvVariable(String, Span),
+    UndefinedFunction(String, Span),
+    UndefinedFilter(String, Span),
+    InvalidArguments(String, Span),
+    InvalidOperand(String, Span),
+    DivisionByZero(Span),
+    InvalidExpression(Span),
+}
+
+impl ResolverError {
+    /// Lokasi error di dalam source code
+    pub fn span(&self) -> Span {
+        match self {
+            Self::UndefinedVariable(_, span)
+            | Self::UndefinedField(_, span)
+            | Self::UndefinedEnvVariable(_, span)
+            | Self::UndefinedFunction(_, span)
+            | Self::UndefinedFilter(_, span)
+            | Self::InvalidArguments(_, span)
+            | Self::InvalidOperand(_, span)
+            | Self::DivisionByZero(span)
+            | Self::InvalidExpression(span) => *span,
+        }
+    }
 }
 
 impl std::error::Error for ResolverError {}
 impl std::fmt::Display for ResolverError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::UndefinedVariable(v) => write!(f, "Undefined variable: {}", v),
-            Self::InvalidExpression => write!(f, "Invalid expression"),
+            Self::UndefinedVariable(v, _) => write!(f, "Undefined variable: {}", v),
+            Self::UndefinedField(v, _) => write!(f, "Undefined field: {}", v),
+            Self::UndefinedEnvVariable(v, _) => {
+                write!(f, "Undefined environment variable: {}", v)
+            }
+            Self::UndefinedFunction(v, _) => write!(f, "Undefined function: {}", v),
+            Self::UndefinedFilter(v, _) => write!(f, "Undefined filter: {}", v),
+            Self::InvalidArguments(v, _) => write!(f, "Invalid arguments: {}", v),
+            Self::InvalidOperand(op, _) => write!(f, "Invalid operands for `{}`", op),
+            Self::DivisionByZero(_) => write!(f, "Division by zero"),
+            Self::InvalidExpression(_) => write!(f, "Invalid expression"),
         }
     }
 }
@@ -32,38 +152,418 @@ impl Resolver {
     pub fn new() -> Self {
         Self {
             variables: HashMap::new(),
+            overrides: HashMap::new(),
+            env_vars: std::env::vars().collect(),
+            stdlib: Stdlib::new(),
+            filters: Filters::new(),
         }
     }
     pub fn resolve_expression(&self, expr: &Expr) -> Result<Value, ResolverError> {
-        match expr {
-            Expr::Number(n) => Ok(Value::Number(*n)),
-            Expr::Identifier(name) => self
-                .variables
+        self.resolve_scoped(expr, &HashMap::new())
+    }
+
+    /// Sama seperti [`Resolver::resolve_expression`], tetapi variabel di `scope`
+    /// akan menimpa variabel global.
+    ///
+    /// dipakai untuk mengevaluasi expression terhadap sebuah response,
+    /// misalnya `body`, `header` dan `status` pada block `CAPTURE`.
+    pub fn resolve_scoped(
+        &self,
+        expr: &Expr,
+        scope: &HashMap<String, Value>,
+    ) -> Result<Value, ResolverError> {
+        match &expr.kind {
+            ExprKind::Number(n) => Ok(Value::Number(*n)),
+            ExprKind::Float(n) => Ok(Value::Float(*n)),
+            ExprKind::Bool(b) => Ok(Value::Bool(*b)),
+            ExprKind::Null => Ok(Value::Null),
+            ExprKind::Identifier(name) => scope
                 .get(name)
+                .or_else(|| self.overrides.get(name))
+                .or_else(|| self.variables.get(name))
                 .cloned()
-                .ok_or(ResolverError::UndefinedVariable(name.clone())),
-            Expr::String(parts) => {
+                .ok_or(ResolverError::UndefinedVariable(name.clone(), expr.span)),
+            ExprKind::String(parts) => {
                 let mut result = String::new();
 
                 for part in parts {
                     match part {
                         StringPart::Text(t) => result.push_str(t),
                         StringPart::Expression(e) => {
-                            let v = self.resolve_expression(e)?;
-                            match v {
-                                Value::String(s) => result.push_str(&s),
-                                Value::Number(n) => result.push_str(&n.to_string()),
-                            }
+                            let v = self.resolve_scoped(e, scope)?;
+                            result.push_str(&v.to_string());
                         }
                     }
                 }
                 Ok(Value::String(result))
             }
+            ExprKind::Array(items) => Ok(Value::Array(
+                items
+                    .iter()
+                    .map(|e| self.resolve_scoped(e, scope))
+                    .collect::<Result<_, _>>()?,
+            )),
+            ExprKind::Object(items) => Ok(Value::Object(
+                items
+                    .iter()
+                    .map(|(k, e)| Ok((k.clone(), self.resolve_scoped(e, scope)?)))
+                    .collect::<Result<_, ResolverError>>()?,
+            )),
+            // `env.NAME`, selama tidak ada variabel bernama `env`
+            ExprKind::Member(target, field)
+                if matches!(&target.kind, ExprKind::Identifier(name) if name == "env"
+                    && !scope.contains_key(name)
+                    && !self.overrides.contains_key(name)
+                    && !self.variables.contains_key(name)) =>
+            {
+                self.env_var(field, None, expr.span)
+            }
+            ExprKind::Member(target, field) => {
+                let target = self.resolve_scoped(target, scope)?;
+                Self::member(target, field)
+                    .ok_or(ResolverError::UndefinedField(field.clone(), expr.span))
+            }
+            ExprKind::Index(target, index) => {
+                let target = self.resolve_scoped(target, scope)?;
+                match self.resolve_scoped(index, scope)? {
+                    Value::Number(i) => Self::index(target, i)
+                        .ok_or(ResolverError::UndefinedField(i.to_string(), expr.span)),
+                    Value::String(field) => Self::member(target, &field)
+                        .ok_or(ResolverError::UndefinedField(field, expr.span)),
+                    _ => Err(ResolverError::InvalidExpression(index.span)),
+                }
+            }
+            ExprKind::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|e| self.resolve_scoped(e, scope))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.call(name, args, expr.span)
+            }
+            ExprKind::Filter(input, name, args) => {
+                let input = match self.resolve_scoped(input, scope) {
+                    // `default` juga berlaku untuk nilai yang tidak terdefinisi
+                    Err(
+                        ResolverError::UndefinedVariable(..)
+                        | ResolverError::UndefinedField(..)
+                        | ResolverError::UndefinedEnvVariable(..),
+                    ) if name == "default" => Value::Null,
+                    input => input?,
+                };
+                let args = args
+                    .iter()
+                    .map(|e| self.resolve_scoped(e, scope))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.filter(name, input, args, expr.span)
+            }
+            ExprKind::Unary(op, operand) => {
+                let value = self.resolve_scoped(operand, scope)?;
+                Self::unary(*op, value)
+                    .ok_or_else(|| ResolverError::InvalidOperand(op.to_string(), expr.span))
+            }
+            // ruas kanan hanya dievaluasi jika dibutuhkan
+            ExprKind::Binary(left, op @ (BinaryOp::And | BinaryOp::Or), right) => {
+                let invalid = || ResolverError::InvalidOperand(op.to_string(), expr.span);
+                let Value::Bool(left) = self.resolve_scoped(left, scope)? else {
+                    return Err(invalid());
+                };
+                if left == (*op == BinaryOp::Or) {
+                    return Ok(Value::Bool(left));
+                }
+                match self.resolve_scoped(right, scope)? {
+                    Value::Bool(right) => Ok(Value::Bool(right)),
+                    _ => Err(invalid()),
+                }
+            }
+            ExprKind::Binary(left, op, right) => {
+                let left = self.resolve_scoped(left, scope)?;
+                let right = self.resolve_scoped(right, scope)?;
+                Self::binary(&left, *op, &right, expr.span)
+            }
+        }
+    }
+
+    fn unary(op: UnaryOp, value: Value) -> Option<Value> {
+        match (op, value) {
+            (UnaryOp::Not, Value::Bool(b)) => Some(Value::Bool(!b)),
+            (UnaryOp::Neg, Value::Number(n)) => n.checked_neg().map(Value::Number),
+            (UnaryOp::Neg, Value::Float(n)) => Some(Value::Float(-n)),
+            _ => None,
+        }
+    }
+
+    /// Mengevaluasi operator biner selain `&&` dan `||`
+    ///
+    /// - `+` pada string menyambung kedua ruas, ruas lainnya boleh berupa
+    ///   angka atau boolean
+    /// - operasi dua `Number` menghasilkan `Number`, termasuk pembagian
+    ///   (dibulatkan ke nol), jika salah satunya `Float` hasilnya `Float`
+    fn binary(
+        left: &Value,
+        op: BinaryOp,
+        right: &Value,
+        span: Span,
+    ) -> Result<Value, ResolverError> {
+        let invalid = || ResolverError::InvalidOperand(op.to_string(), span);
+
+        if let BinaryOp::Compare(op) = op {
+            return Self::compare(left, op, right)
+                .map(Value::Bool)
+                .ok_or_else(invalid);
+        }
+
+        match (left, op, right) {
+            (Value::String(_), BinaryOp::Add, _) | (_, BinaryOp::Add, Value::String(_)) => {
+                let scalar = |v: &Value| {
+                    matches!(
+                        v,
+                        Value::String(_) | Value::Number(_) | Value::Float(_) | Value::Bool(_)
+                    )
+                };
+                if !scalar(left) || !scalar(right) {
+                    return Err(invalid());
+                }
+                Ok(Value::String(format!("{}{}", left, right)))
+            }
+            (Value::Number(l), _, Value::Number(r)) => {
+                let (l, r) = (*l, *r);
+                if matches!(op, BinaryOp::Div | BinaryOp::Rem) && r == 0 {
+                    return Err(ResolverError::DivisionByZero(span));
+                }
+                let result = match op {
+                    BinaryOp::Add => l.checked_add(r),
+                    BinaryOp::Sub => l.checked_sub(r),
+                    BinaryOp::Mul => l.checked_mul(r),
+                    BinaryOp::Div => l.checked_div(r),
+                    BinaryOp::Rem => l.checked_rem(r),
+                    _ => None,
+                };
+                result.map(Value::Number).ok_or_else(invalid)
+            }
+            _ => {
+                let (Some(l), Some(r)) = (Self::as_f64(left), Self::as_f64(right)) else {
+                    return Err(invalid());
+                };
+                if matches!(op, BinaryOp::Div | BinaryOp::Rem) && r == 0.0 {
+                    return Err(ResolverError::DivisionByZero(span));
+                }
+                Ok(Value::Float(match op {
+                    BinaryOp::Add => l + r,
+                    BinaryOp::Sub => l - r,
+                    BinaryOp::Mul => l * r,
+                    BinaryOp::Div => l / r,
+                    BinaryOp::Rem => l % r,
+                    _ => return Err(invalid()),
+                }))
+            }
         }
     }
+
+    /// Menjalankan fungsi dari [`Resolver::stdlib`]
+    fn call(&self, name: &str, args: Vec<Value>, span: Span) -> Result<Value, ResolverError> {
+        let function = self
+            .stdlib
+            .get(name)
+            .ok_or_else(|| ResolverError::UndefinedFunction(name.to_string(), span))?;
+        let checked: Vec<Option<&Value>> = args.iter().map(Some).collect();
+        function
+            .check_args(&checked)
+            .map_err(|e| ResolverError::InvalidArguments(e, span))?;
+
+        (function.call)(self, &args, span)
+    }
+
+    /// Filter dari [`Resolver::filters`], atau fungsi dari [`Resolver::stdlib`]
+    /// jika tidak ada filter dengan nama tersebut
+    pub fn filter_function(&self, name: &str) -> Option<&Function> {
+        self.filters.get(name).or_else(|| self.stdlib.get(name))
+    }
+
+    /// Menjalankan filter dengan `input` sebagai argumen pertama
+    ///
+    /// angka dan boolean diubah menjadi string jika filter membutuhkan string,
+    /// sehingga `${page | urlencode}` tetap bisa dipakai.
+    fn filter(
+        &self,
+        name: &str,
+        input: Value,
+        args: Vec<Value>,
+        span: Span,
+    ) -> Result<Value, ResolverError> {
+        let function = self
+            .filter_function(name)
+            .ok_or_else(|| ResolverError::UndefinedFilter(name.to_string(), span))?;
+
+        let input = match input {
+            Value::Number(_) | Value::Float(_) | Value::Bool(_)
+                if function.params.first() == Some(&ParamType::String) =>
+            {
+                Value::String(input.to_string())
+            }
+            input => input,
+        };
+        let args: Vec<Value> = std::iter::once(input).chain(args).collect();
+        let checked: Vec<Option<&Value>> = args.iter().map(Some).collect();
+        function
+            .check_args(&checked)
+            .map_err(|e| ResolverError::InvalidArguments(e, span))?;
+
+        (function.call)(self, &args, span)
+    }
+
+    /// Helper untuk mengambil environment variable
+    pub(crate) fn env_var(
+        &self,
+        key: &str,
+        default: Option<&Value>,
+        span: Span,
+    ) -> Result<Value, ResolverError> {
+        match (self.env_vars.get(key), default) {
+            (Some(v), _) => Ok(Value::String(v.clone())),
+            (None, Some(default)) => Ok(default.clone()),
+            (None, None) => Err(ResolverError::UndefinedEnvVariable(key.to_string(), span)),
+        }
+    }
+
+    /// Helper untuk mengambil field dari sebuah object
+    ///
+    /// jika tidak ada field dengan nama yang sama persis, pencarian diulang
+    /// tanpa membedakan huruf besar/kecil, sehingga `header.ETag` tetap
+    /// menemukan header `etag`.
+    ///
+    /// field `length` pada array, string dan object (yang tidak memiliki
+    /// field `length`) mengembalikan jumlah item di dalamnya.
+    fn member(target: Value, field: &str) -> Option<Value> {
+        match target {
+            Value::Object(items) => {
+                let position = items.iter().position(|(k, _)| k == field).or_else(|| {
+                    items
+                        .iter()
+                        .position(|(k, _)| k.eq_ignore_ascii_case(field))
+                });
+
+                match position {
+                    Some(position) => items.into_iter().nth(position).map(|(_, v)| v),
+                    None if field == "length" => Some(Value::Number(items.len() as i64)),
+                    None => None,
+                }
+            }
+            Value::Array(items) if field == "length" => Some(Value::Number(items.len() as i64)),
+            Value::String(s) if field == "length" => Some(Value::Number(s.chars().count() as i64)),
+            _ => None,
+        }
+    }
+
+    /// Helper untuk mengambil item dari sebuah array
+    fn index(target: Value, index: i64) -> Option<Value> {
+        let Value::Array(items) = target else {
+            return None;
+        };
+
+        usize::try_from(index)
+            .ok()
+            .and_then(|i| items.into_iter().nth(i))
+    }
+
+    /// Mengevaluasi sebuah assertion terhadap `scope` sebuah response
+    ///
+    /// # Return
+    /// Tuple `(passed, actual)`
+    /// - `passed` hasil perbandingan
+    /// - `actual` nilai ruas kiri, dipakai untuk pesan error
+    pub fn resolve_assertion(
+        &self,
+        assertion: &Assertion,
+        scope: &HashMap<String, Value>,
+    ) -> Result<(bool, Value), ResolverError> {
+        let left = self.resolve_scoped(&assertion.left, scope)?;
+        let right = self.resolve_scoped(&assertion.right, scope)?;
+
+        let passed = Self::compare(&left, assertion.op, &right).ok_or(
+            ResolverError::InvalidOperand(assertion.op.to_string(), assertion.span),
+        )?;
+
+        Ok((passed, left))
+    }
+
+    /// Membandingkan dua value dengan operator `op`
+    ///
+    /// mengembalikan `None` jika tipe kedua value tidak bisa dibandingkan
+    /// dengan operator tersebut.
+    pub fn compare(left: &Value, op: CompareOp, right: &Value) -> Option<bool> {
+        match op {
+            CompareOp::Eq => Some(Self::equals(left, right)),
+            CompareOp::NotEq => Some(!Self::equals(left, right)),
+            CompareOp::Contains => match (left, right) {
+                (Value::String(l), Value::String(r)) => Some(l.contains(r.as_str())),
+                (Value::Array(items), v) => Some(items.iter().any(|i| Self::equals(i, v))),
+                (Value::Object(items), Value::String(k)) => {
+                    Some(items.iter().any(|(key, _)| key == k))
+                }
+                _ => None,
+            },
+            CompareOp::Lt | CompareOp::LtEq | CompareOp::Gt | CompareOp::GtEq => {
+                let ordering = match (left, right) {
+                    (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+                    _ => Self::as_f64(left)?.partial_cmp(&Self::as_f64(right)?),
+                }?;
+
+                Some(match op {
+                    CompareOp::Lt => ordering.is_lt(),
+                    CompareOp::LtEq => ordering.is_le(),
+                    CompareOp::Gt => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                })
+            }
+        }
+    }
+
+    /// Helper untuk membandingkan kesamaan dua value,
+    /// `Number` dan `Float` dianggap setara jika nilainya sama
+    fn equals(left: &Value, right: &Value) -> bool {
+        match (Self::as_f64(left), Self::as_f64(right)) {
+            (Some(l), Some(r)) => l == r,
+            _ => left == right,
+        }
+    }
+
+    fn as_f64(value: &Value) -> Option<f64> {
+        match value {
+            Value::Number(n) => Some(*n as f64),
+            Value::Float(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Mengevaluasi block `CAPTURE` terhadap `scope` sebuah response,
+    /// lalu menyimpan hasilnya sebagai variabel global.
+    pub fn resolve_captures(
+        &mut self,
+        captures: &[(String, Expr)],
+        scope: &HashMap<String, Value>,
+    ) -> Result<(), ResolverError> {
+        for (name, expr) in captures {
+            let v = self.resolve_scoped(expr, scope)?;
+            self.variables.insert(name.clone(), v);
+        }
+        Ok(())
+    }
+
+    /// Mengisi variabel dari block `env` yang dipilih
+    ///
+    /// dipanggil sebelum statement lain di-resolve, sehingga assignment di
+    /// dalam script tetap bisa menimpa nilai dari environment.
+    pub fn resolve_env(&mut self, assignments: &[(String, Expr)]) -> Result<(), ResolverError> {
+        for (name, expr) in assignments {
+            let v = self.resolve_expression(expr)?;
+            self.variables.insert(name.clone(), v);
+        }
+        Ok(())
+    }
+
     pub fn resolve_statement(&mut self, stmt: &Stmt) -> Result<(), ResolverError> {
         match stmt {
-            Stmt::Assignment { name, value } => {
+            Stmt::Assignment { name, value, .. } => {
                 let v = self.resolve_expression(value)?;
                 self.variables.insert(name.clone(), v);
                 Ok(())
@@ -72,6 +572,10 @@ impl Resolver {
                 let _url = self.resolve_expression(&req.url)?;
                 Ok(())
             }
+            // environment hanya di-resolve jika dipilih, lihat [`Resolver::resolve_env`]
+            Stmt::Env { .. } => Ok(()),
+            // import sudah diganti dengan isi file-nya saat script dimuat
+            Stmt::Import { .. } => Ok(()),
         }
     }
 }
@@ -1,8 +1,12 @@
 use std::collections::HashSet;
 
 use crate::{
-    ast::{Req, Stmt},
+    ast::{Expr, ExprKind, Req, Stmt},
+    auth::Auth,
+    options::RequestOptions,
     resolver::{Resolver, Value},
+    span::Span,
+    token::HttpMethod,
 };
 
 pub struct SemanticChecker<'a> {
@@ -12,10 +16,39 @@ pub struct SemanticChecker<'a> {
 
 #[derive(Debug)]
 pub enum SemanticError {
-    BodyNotAllowed(String),
-    HeaderValueMustBeString,
-    UrlMustBeString,
-    DuplicateLabel(String),
+    BodyNotAllowed(String, Span),
+    HeaderValueMustBeString(Span),
+    UrlMustBeString(Span),
+    DuplicateLabel(String, Span),
+    InvalidMethod(String, Span),
+    /// Option di section `OPTIONS` tidak dikenal atau nilainya salah
+    InvalidOption(String, Span),
+    /// Skema di section `AUTH` tidak dikenal atau field-nya salah
+    InvalidAuth(String, Span),
+    /// Fungsi yang dipanggil tidak ada di [`crate::stdlib::Stdlib`]
+    UndefinedFunction(String, Span),
+    /// Filter tidak ada di [`crate::filters::Filters`] maupun stdlib
+    UndefinedFilter(String, Span),
+    /// Jumlah atau tipe argumen fungsi salah
+    InvalidArguments(String, Span),
+}
+
+impl SemanticError {
+    /// Lokasi error di dalam source code
+    pub fn span(&self) -> Span {
+        match self {
+            Self::BodyNotAllowed(_, span)
+            | Self::HeaderValueMustBeString(span)
+            | Self::UrlMustBeString(span)
+            | Self::DuplicateLabel(_, span)
+            | Self::InvalidMethod(_, span)
+            | Self::InvalidOption(_, span)
+            | Self::InvalidAuth(_, span)
+            | Self::UndefinedFunction(_, span)
+            | Self::UndefinedFilter(_, span)
+            | Self::InvalidArguments(_, span) => *span,
+        }
+    }
 }
 
 impl std::error::Error for SemanticError {}
@@ -23,10 +56,16 @@ impl std::error::Error for SemanticError {}
 impl std::fmt::Display for SemanticError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::BodyNotAllowed(_) => write!(f, "Body not allowed"),
-            Self::DuplicateLabel(l) => write!(f, "Duplicate label: {}", l),
-            Self::HeaderValueMustBeString => write!(f, "Headers value must be string"),
-            Self::UrlMustBeString => write!(f, "Url must be string"),
+            Self::BodyNotAllowed(_, _) => write!(f, "Body not allowed"),
+            Self::DuplicateLabel(l, _) => write!(f, "Duplicate label: {}", l),
+            Self::HeaderValueMustBeString(_) => write!(f, "Headers value must be string"),
+            Self::UrlMustBeString(_) => write!(f, "Url must be string"),
+            Self::InvalidMethod(m, _) => write!(f, "Invalid HTTP method: {}", m),
+            Self::InvalidOption(m, _) => write!(f, "Invalid option: {}", m),
+            Self::InvalidAuth(m, _) => write!(f, "Invalid auth: {}", m),
+            Self::UndefinedFunction(name, _) => write!(f, "Undefined function: {}", name),
+            Self::UndefinedFilter(name, _) => write!(f, "Undefined filter: {}", name),
+            Self::InvalidArguments(m, _) => write!(f, "Invalid arguments: {}", m),
         }
     }
 }
@@ -44,36 +83,135 @@ impl<'a> SemanticChecker<'a> {
         let label = &req.label;
         // if let Some(label) = &req.label {
         if !self.labels.insert(label.clone()) {
-            return Err(SemanticError::DuplicateLabel(label.clone()));
+            return Err(SemanticError::DuplicateLabel(label.clone(), req.span));
         }
         // }
 
         // cek url harus string
-        match self.resolver.resolve_expression(&req.url).unwrap() {
-            Value::String(_) => {}
-            _ => return Err(SemanticError::UrlMustBeString),
+        // expression yang belum bisa di-resolve (misalnya bergantung pada
+        // hasil CAPTURE) baru bisa dicek saat request dijalankan
+        if let Ok(v) = self.resolver.resolve_expression(&req.url)
+            && !matches!(v, Value::String(_))
+        {
+            return Err(SemanticError::UrlMustBeString(req.url.span));
         }
 
-        // cek tidak boleh ada body di get/delete
-        if matches!(req.method.as_str(), "GET" | "DELETE") && req.body.is_some() {
-            return Err(SemanticError::BodyNotAllowed(req.method.clone()));
+        // cek nama method valid sebagai token HTTP
+        if !req.method.is_valid() {
+            return Err(SemanticError::InvalidMethod(
+                req.method.to_string(),
+                req.span,
+            ));
+        }
+
+        // cek tidak boleh ada body di get/delete/head
+        if matches!(
+            req.method,
+            HttpMethod::GET | HttpMethod::DELETE | HttpMethod::HEAD
+        ) && req.body.is_some()
+        {
+            return Err(SemanticError::BodyNotAllowed(
+                req.method.to_string(),
+                req.span,
+            ));
         }
 
         // cek header value harus string
         for (_, expr) in &req.headers {
-            let v = self.resolver.resolve_expression(expr).unwrap();
-            if !matches!(v, Value::String(_)) {
-                return Err(SemanticError::HeaderValueMustBeString);
+            if let Ok(v) = self.resolver.resolve_expression(expr)
+                && !matches!(v, Value::String(_))
+            {
+                return Err(SemanticError::HeaderValueMustBeString(expr.span));
+            }
+        }
+
+        // cek nama dan tipe setiap option
+        let mut options = RequestOptions::default();
+        for (name, expr) in &req.options {
+            let value = match self.resolver.resolve_expression(expr) {
+                Ok(value) => value,
+                Err(_) if RequestOptions::NAMES.contains(&name.as_str()) => continue,
+                // nama yang tidak dikenal tetap dilaporkan
+                Err(_) => Value::Null,
+            };
+            options
+                .set(name, &value)
+                .map_err(|e| SemanticError::InvalidOption(e, expr.span))?;
+        }
+
+        // cek skema auth dan field-nya
+        if let Some((scheme, expr)) = &req.auth {
+            let value = match self.resolver.resolve_expression(expr) {
+                Ok(value) => Some(value),
+                Err(_) if Auth::SCHEMES.contains(&scheme.as_str()) => None,
+                Err(_) => Some(Value::Null),
+            };
+            if let Some(value) = value {
+                Auth::new(scheme, &value).map_err(|e| SemanticError::InvalidAuth(e, expr.span))?;
             }
         }
 
         Ok(())
     }
 
+    /// Mengecek setiap pemanggilan fungsi dan filter di dalam `expr`
+    ///
+    /// nama fungsi harus terdaftar, jumlah argumen harus sesuai, dan tipe
+    /// argumen yang sudah bisa di-resolve harus sesuai. Nilai sebelum `|`
+    /// pada filter hanya dihitung jumlahnya.
+    fn check_calls(&self, expr: &Expr) -> Result<(), SemanticError> {
+        let mut result = Ok(());
+        expr.walk(&mut |expr| {
+            if result.is_err() {
+                return;
+            }
+            let (function, piped, args) = match &expr.kind {
+                ExprKind::Call(name, args) => match self.resolver.stdlib.get(name) {
+                    Some(function) => (function, false, args),
+                    None => {
+                        result = Err(SemanticError::UndefinedFunction(name.clone(), expr.span));
+                        return;
+                    }
+                },
+                ExprKind::Filter(_, name, args) => match self.resolver.filter_function(name) {
+                    Some(function) => (function, true, args),
+                    None => {
+                        result = Err(SemanticError::UndefinedFilter(name.clone(), expr.span));
+                        return;
+                    }
+                },
+                _ => return,
+            };
+
+            let values: Vec<Option<Value>> = piped
+                .then_some(None)
+                .into_iter()
+                .chain(
+                    args.iter()
+                        .map(|arg| self.resolver.resolve_expression(arg).ok()),
+                )
+                .collect();
+            let values: Vec<Option<&Value>> = values.iter().map(Option::as_ref).collect();
+            if let Err(e) = function.check_args(&values) {
+                result = Err(SemanticError::InvalidArguments(e, expr.span));
+            }
+        });
+        result
+    }
+
     pub fn check_statement(&mut self, stmt: &Stmt) -> Result<(), SemanticError> {
         match stmt {
-            Stmt::Request(req) => self.check_request(req),
-            _ => Ok(()),
+            Stmt::Request(req) => {
+                for expr in req.expressions() {
+                    self.check_calls(expr)?;
+                }
+                self.check_request(req)
+            }
+            Stmt::Assignment { value, .. } => self.check_calls(value),
+            Stmt::Env { assignments, .. } => assignments
+                .iter()
+                .try_for_each(|(_, value)| self.check_calls(value)),
+            Stmt::Import { .. } => Ok(()),
         }
     }
 }
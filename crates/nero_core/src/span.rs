@@ -0,0 +1,39 @@
+/// Lokasi sebuah token atau node AST di dalam source code.
+///
+/// `start` dan `end` adalah byte offset (end bersifat eksklusif),
+/// sedangkan `line` dan `column` dimulai dari 1 dan menunjuk ke
+/// karakter pertama dari span.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Span {
+    /// Byte offset awal (inklusif)
+    pub start: usize,
+    /// Byte offset akhir (eksklusif)
+    pub end: usize,
+    /// Nomor baris, dimulai dari 1
+    pub line: usize,
+    /// Nomor kolom dalam hitungan karakter, dimulai dari 1
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Self {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    /// Menggabungkan dua span menjadi satu span yang mencakup keduanya.
+    ///
+    /// `self` diasumsikan berada sebelum `other` di dalam source code.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            start: self.start,
+            end: other.end.max(self.end),
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+
+use base64::Engine;
+use chrono::format::{Item, StrftimeItems};
+use hmac::{Hmac, Mac};
+use md5::Md5;
+use rand::Rng;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use crate::{
+    resolver::{Resolver, ResolverError, Value},
+    span::Span,
+};
+
+/// Tipe parameter fungsi bawaan
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ParamType {
+    Any,
+    String,
+    Number,
+    Object,
+}
+
+impl ParamType {
+    fn accepts(&self, value: &Value) -> bool {
+        match self {
+            ParamType::Any => true,
+            ParamType::String => matches!(value, Value::String(_)),
+            ParamType::Number => matches!(value, Value::Number(_)),
+            ParamType::Object => matches!(value, Value::Object(_)),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            ParamType::Any => "any",
+            ParamType::String => "string",
+            ParamType::Number => "number",
+            ParamType::Object => "object",
+        }
+    }
+}
+
+/// Implementasi fungsi bawaan, argumennya sudah dicek dengan
+/// [`Function::check_args`]
+pub type Builtin = fn(&Resolver, &[Value], Span) -> Result<Value, ResolverError>;
+
+/// Fungsi yang bisa dipanggil dari expression, contoh: `uuid()`
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: &'static str,
+    /// Tipe setiap parameter, berurutan
+    pub params: &'static [ParamType],
+    /// Jumlah parameter wajib, sisanya opsional
+    pub required: usize,
+    pub call: Builtin,
+}
+
+impl Function {
+    /// Bentuk pemanggilan untuk pesan error, contoh: `now(string?)`
+    pub fn signature(&self) -> String {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let optional = if i >= self.required { "?" } else { "" };
+                format!("{}{}", p.as_str(), optional)
+            })
+            .collect();
+        format!("{}({})", self.name, params.join(", "))
+    }
+
+    /// Mengecek jumlah dan tipe argumen
+    ///
+    /// argumen bernilai `None` belum bisa di-resolve, misalnya karena
+    /// bergantung pada hasil `CAPTURE`, sehingga hanya jumlahnya yang dicek.
+    ///
+    /// # Return
+    /// pesan error jika argumen tidak sesuai dengan [`Function::signature`]
+    pub fn check_args(&self, args: &[Option<&Value>]) -> Result<(), String> {
+        if args.len() < self.required || args.len() > self.params.len() {
+            return Err(format!(
+                "`{}` expects {}, found {} argument(s)",
+                self.name,
+                self.signature(),
+                args.len()
+            ));
+        }
+
+        for (i, (param, arg)) in self.params.iter().zip(args).enumerate() {
+            if let Some(arg) = arg
+                && !param.accepts(arg)
+            {
+                return Err(format!(
+                    "argument {} of `{}` must be a {}, found `{}`",
+                    i + 1,
+                    self.name,
+                    param.as_str(),
+                    arg
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Daftar fungsi yang bisa dipanggil dari expression
+///
+/// berisi fungsi bawaan berikut, dan bisa ditambah dengan [`Stdlib::register`]:
+///
+/// ```text
+/// env("NAME", default?)   environment variable
+/// uuid()                  UUID v4 acak
+/// now(format?)            waktu sekarang (UTC), default RFC 3339,
+///                         contoh format: "%Y-%m-%d"
+/// timestamp(unit?)        unix timestamp dalam "s" (default) atau "ms"
+/// random(min?, max?)      bilangan bulat acak, `random(max)` atau `random(min, max)`
+/// base64(text)            encode base64
+/// urlencode(text)         percent-encoding untuk query atau path
+/// hex(text)               encode hex
+/// sha256(text, enc?)      hash SHA-256
+/// md5(text, enc?)         hash MD5
+/// hmac_sha256(key, text, enc?)
+///                         HMAC-SHA256
+/// jwt_sign(claims, secret, alg?)
+///                         JWT dengan algoritma "HS256" (default), "HS384" atau "HS512"
+/// ```
+///
+/// `enc` adalah encoding hasil hash, "hex" (default) atau "base64".
+#[derive(Debug, Clone)]
+pub struct Stdlib {
+    functions: HashMap<&'static str, Function>,
+}
+
+impl Default for Stdlib {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Stdlib {
+    /// Stdlib yang berisi semua fungsi bawaan
+    pub fn new() -> Self {
+        let mut stdlib = Self {
+            functions: HashMap::new(),
+        };
+
+        use ParamType::*;
+        let builtins: [(&'static str, &'static [ParamType], usize, Builtin); 12] = [
+            ("env", &[String, Any], 1, env),
+            ("uuid", &[], 0, uuid),
+            ("now", &[String], 0, now),
+            ("timestamp", &[String], 0, timestamp),
+            ("random", &[Number, Number], 0, random),
+            ("base64", &[String], 1, base64),
+            ("urlencode", &[String], 1, urlencode),
+            ("hex", &[String], 1, hex),
+            ("sha256", &[String, String], 1, sha256),
+            ("md5", &[String, String], 1, md5),
+            ("hmac_sha256", &[String, String, String], 2, hmac_sha256),
+            ("jwt_sign", &[Object, String, String], 2, jwt_sign),
+        ];
+        for (name, params, required, call) in builtins {
+            stdlib.register(Function {
+                name,
+                params,
+                required,
+                call,
+            });
+        }
+
+        stdlib
+    }
+
+    /// Menambahkan fungsi, menimpa fungsi lain dengan nama yang sama
+    pub fn register(&mut self, function: Function) {
+        self.functions.insert(function.name, function);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Function> {
+        self.functions.get(name)
+    }
+}
+
+/// Helper untuk membuat [`ResolverError::InvalidArguments`]
+fn invalid(message: String, span: Span) -> ResolverError {
+    ResolverError::InvalidArguments(message, span)
+}
+
+fn env(resolver: &Resolver, args: &[Value], span: Span) -> Result<Value, ResolverError> {
+    match args {
+        [Value::String(key)] => resolver.env_var(key, None, span),
+        [Value::String(key), default] => resolver.env_var(key, Some(default), span),
+        _ => unreachable!("checked by Function::check_args"),
+    }
+}
+
+fn uuid(_: &Resolver, _: &[Value], _: Span) -> Result<Value, ResolverError> {
+    let mut bytes: [u8; 16] = rand::random();
+    // versi 4 dan variant RFC 4122
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = to_hex(&bytes);
+    Ok(Value::String(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )))
+}
+
+fn now(_: &Resolver, args: &[Value], span: Span) -> Result<Value, ResolverError> {
+    let now = chrono::Utc::now();
+    match args {
+        [] => Ok(Value::String(now.to_rfc3339())),
+        [Value::String(format)] => {
+            // format yang tidak valid membuat chrono panic saat ditampilkan
+            if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                return Err(invalid(
+                    format!("invalid `now` format \"{}\"", format),
+                    span,
+                ));
+            }
+            Ok(Value::String(now.format(format).to_string()))
+        }
+        _ => unreachable!("checked by Function::check_args"),
+    }
+}
+
+fn timestamp(_: &Resolver, args: &[Value], span: Span) -> Result<Value, ResolverError> {
+    let now = chrono::Utc::now();
+    match args.first() {
+        None => Ok(Value::Number(now.timestamp())),
+        Some(Value::String(unit)) if unit == "s" => Ok(Value::Number(now.timestamp())),
+        Some(Value::String(unit)) if unit == "ms" => Ok(Value::Number(now.timestamp_millis())),
+        Some(unit) => Err(invalid(
+            format!(
+                "`timestamp` unit must be \"s\" or \"ms\", found \"{}\"",
+                unit
+            ),
+            span,
+        )),
+    }
+}
+
+fn random(_: &Resolver, args: &[Value], span: Span) -> Result<Value, ResolverError> {
+    let (min, max) = match args {
+        [] => (0, i32::MAX as i64),
+        [Value::Number(max)] => (0, *max),
+        [Value::Number(min), Value::Number(max)] => (*min, *max),
+        _ => unreachable!("checked by Function::check_args"),
+    };
+    if min > max {
+        return Err(invalid(
+            format!("`random` range is empty: {} > {}", min, max),
+            span,
+        ));
+    }
+    Ok(Value::Number(rand::thread_rng().gen_range(min..=max)))
+}
+
+fn base64(_: &Resolver, args: &[Value], _: Span) -> Result<Value, ResolverError> {
+    let [Value::String(text)] = args else {
+        unreachable!("checked by Function::check_args");
+    };
+    Ok(Value::String(
+        base64::engine::general_purpose::STANDARD.encode(text),
+    ))
+}
+
+/// Percent-encoding RFC 3986, hanya karakter unreserved yang tidak di-encode
+fn urlencode(_: &Resolver, args: &[Value], _: Span) -> Result<Value, ResolverError> {
+    let [Value::String(text)] = args else {
+        unreachable!("checked by Function::check_args");
+    };
+    let encoded = text
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect();
+    Ok(Value::String(encoded))
+}
+
+fn hex(_: &Resolver, args: &[Value], _: Span) -> Result<Value, ResolverError> {
+    let [Value::String(text)] = args else {
+        unreachable!("checked by Function::check_args");
+    };
+    Ok(Value::String(to_hex(text.as_bytes())))
+}
+
+fn sha256(_: &Resolver, args: &[Value], span: Span) -> Result<Value, ResolverError> {
+    let Value::String(text) = &args[0] else {
+        unreachable!("checked by Function::check_args");
+    };
+    encode("sha256", &Sha256::digest(text), args.get(1), span)
+}
+
+fn md5(_: &Resolver, args: &[Value], span: Span) -> Result<Value, ResolverError> {
+    let Value::String(text) = &args[0] else {
+        unreachable!("checked by Function::check_args");
+    };
+    encode("md5", &Md5::digest(text), args.get(1), span)
+}
+
+fn hmac_sha256(_: &Resolver, args: &[Value], span: Span) -> Result<Value, ResolverError> {
+    let (Value::String(key), Value::String(text)) = (&args[0], &args[1]) else {
+        unreachable!("checked by Function::check_args");
+    };
+    let signature = hmac::<Hmac<Sha256>>(key.as_bytes(), text.as_bytes());
+    encode("hmac_sha256", &signature, args.get(2), span)
+}
+
+/// JWT dengan header `{"alg": alg, "typ": "JWT"}` dan payload `claims`
+fn jwt_sign(_: &Resolver, args: &[Value], span: Span) -> Result<Value, ResolverError> {
+    let (claims, Value::String(secret)) = (&args[0], &args[1]) else {
+        unreachable!("checked by Function::check_args");
+    };
+    let alg = match args.get(2) {
+        Some(Value::String(alg)) => alg.as_str(),
+        _ => "HS256",
+    };
+
+    let engine = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    let header = serde_json::json!({ "alg": alg, "typ": "JWT" });
+    let input = format!(
+        "{}.{}",
+        engine.encode(header.to_string()),
+        engine.encode(claims.to_json().to_string())
+    );
+
+    let key = secret.as_bytes();
+    let signature = match alg {
+        "HS256" => hmac::<Hmac<Sha256>>(key, input.as_bytes()),
+        "HS384" => hmac::<Hmac<Sha384>>(key, input.as_bytes()),
+        "HS512" => hmac::<Hmac<Sha512>>(key, input.as_bytes()),
+        _ => {
+            return Err(invalid(
+                format!(
+                    "`jwt_sign` algorithm must be \"HS256\", \"HS384\" or \"HS512\", found \"{}\"",
+                    alg
+                ),
+                span,
+            ));
+        }
+    };
+
+    Ok(Value::String(format!(
+        "{}.{}",
+        input,
+        engine.encode(signature)
+    )))
+}
+
+fn hmac<M: Mac + hmac::digest::KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+    // HMAC menerima key dengan panjang berapapun
+    let mut mac =
+        <M as hmac::digest::KeyInit>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Mengubah hasil hash menjadi string sesuai encoding `enc`
+fn encode(
+    name: &str,
+    bytes: &[u8],
+    enc: Option<&Value>,
+    span: Span,
+) -> Result<Value, ResolverError> {
+    match enc {
+        None => Ok(Value::String(to_hex(bytes))),
+        Some(Value::String(enc)) if enc == "hex" => Ok(Value::String(to_hex(bytes))),
+        Some(Value::String(enc)) if enc == "base64" => Ok(Value::String(
+            base64::engine::general_purpose::STANDARD.encode(bytes),
+        )),
+        Some(enc) => Err(invalid(
+            format!(
+                "`{}` encoding must be \"hex\" or \"base64\", found \"{}\"",
+                name, enc
+            ),
+            span,
+        )),
+    }
+}
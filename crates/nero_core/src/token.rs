@@ -1,21 +1,81 @@
+use crate::span::Span;
+
 /// Daftar HTTP Method yang didukung
 ///
-/// jika sebuah method tidak dikenali, maka akan digunakan [`HttpMethod::UNKNOWN`].
-// #[derive(Debug, PartialEq, Clone)]
-// pub enum HttpMethod {
-//     /// HTTP GET request
-//     GET,
-//     /// HTTP POST request
-//     POST,
-//     /// HTTP PUT request
-//     PUT,
-//     /// HTTP PATCH request
-//     PATCH,
-//     /// HTTP DELETE request
-//     DELETE,
-//     /// Method yang tidak dikenal oleh lexer
-//     UNKNOWN,
-// }
+/// nama method tidak membedakan huruf besar/kecil, `@get` sama dengan `@GET`.
+/// method di luar daftar ini (misalnya `PURGE`) akan menjadi [`HttpMethod::CUSTOM`].
+#[derive(Debug, PartialEq, Clone)]
+pub enum HttpMethod {
+    /// HTTP GET request
+    GET,
+    /// HTTP POST request
+    POST,
+    /// HTTP PUT request
+    PUT,
+    /// HTTP PATCH request
+    PATCH,
+    /// HTTP DELETE request
+    DELETE,
+    /// HTTP HEAD request
+    HEAD,
+    /// HTTP OPTIONS request
+    OPTIONS,
+    /// HTTP CONNECT request
+    CONNECT,
+    /// HTTP TRACE request
+    TRACE,
+    /// Extension method, disimpan dalam huruf besar
+    CUSTOM(String),
+}
+
+impl HttpMethod {
+    pub fn as_str(&self) -> &str {
+        match self {
+            HttpMethod::GET => "GET",
+            HttpMethod::POST => "POST",
+            HttpMethod::PUT => "PUT",
+            HttpMethod::PATCH => "PATCH",
+            HttpMethod::DELETE => "DELETE",
+            HttpMethod::HEAD => "HEAD",
+            HttpMethod::OPTIONS => "OPTIONS",
+            HttpMethod::CONNECT => "CONNECT",
+            HttpMethod::TRACE => "TRACE",
+            HttpMethod::CUSTOM(m) => m,
+        }
+    }
+
+    /// Cek apakah nama method valid sebagai token HTTP (RFC 9110)
+    pub fn is_valid(&self) -> bool {
+        let name = self.as_str();
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+    }
+}
+
+impl From<&str> for HttpMethod {
+    fn from(method: &str) -> Self {
+        match method.to_uppercase().as_str() {
+            "GET" => HttpMethod::GET,
+            "POST" => HttpMethod::POST,
+            "PUT" => HttpMethod::PUT,
+            "PATCH" => HttpMethod::PATCH,
+            "DELETE" => HttpMethod::DELETE,
+            "HEAD" => HttpMethod::HEAD,
+            "OPTIONS" => HttpMethod::OPTIONS,
+            "CONNECT" => HttpMethod::CONNECT,
+            "TRACE" => HttpMethod::TRACE,
+            m => HttpMethod::CUSTOM(m.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
 
 /// Daftar token yang dapat dihasilkan oleh lexer dari source code.
 ///
@@ -30,33 +90,70 @@ pub enum Token {
     Hash,
     /// Karakter `=`
     Equals,
+    /// `==`
+    EqualEqual,
+    /// `!=`
+    NotEqual,
+    /// `<`
+    Less,
+    /// `<=`
+    LessEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterEqual,
     /// Karakter `:`
     Colon,
     /// Karakter `;`
     SemiColon,
     /// Karakter `,`
     Comma,
+    /// Karakter `-`
+    /// digunakan untuk pengurangan dan angka negatif.
+    Minus,
+    /// Karakter `+`
+    Plus,
+    /// Karakter `*`
+    Star,
+    /// Karakter `/`
+    Slash,
+    /// Karakter `%`
+    Percent,
+    /// Karakter `!`
+    Bang,
+    /// `&&`
+    AndAnd,
+    /// `||`
+    OrOr,
+    /// Karakter `|`
+    /// digunakan untuk filter, contoh: `${name | upper}`.
+    Pipe,
+    /// Karakter `.`
+    /// digunakan untuk mengakses field, contoh: `body.data.token`.
+    Dot,
     /// `{`
     OpenBrace,
     /// `}`
     CloseBrace,
     /// `[`
     OpenBracket,
+    /// `(`
+    OpenParen,
+    /// `)`
+    CloseParen,
     /// `]`
     CloseBracket,
 
     TemplateStart,
     TemplateEnd,
 
+    /// Keyword `import`
+    /// digunakan untuk memuat file `.ns` lain, contoh: `import "common.ns";`.
+    Import,
+
     /// Identifier umum, misalnya nama variabel atau nama field.
     Identifier(String),
 
-    /// HTTP method.
-    ///
-    /// cek enum [`HttpMethod`] untuk melihat datfar
-    /// method yang didukung.
-    // Method(HttpMethod),
-
     /// Literal string `"..."`.
     StringLiteral(String),
 
@@ -65,4 +162,21 @@ pub enum Token {
 
     /// Literal angka, contoh: `123` atau `42`.
     NumberLiteral(i64),
+
+    /// Literal angka desimal, contoh: `3.14` atau `1e3`.
+    FloatLiteral(f64),
+
+    /// Doc comment `/// ...`, berisi teks setelah `///`.
+    /// digunakan untuk mendokumentasikan request setelahnya.
+    DocComment(String),
+}
+
+/// Token beserta lokasinya di dalam source code.
+///
+/// dihasilkan oleh [`crate::lexer::Lexer::tokenize`] dan dipakai oleh parser
+/// agar setiap error bisa menunjuk ke baris dan kolom yang tepat.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
 }
@@ -0,0 +1,124 @@
+use std::{fs, path::Path};
+
+use nero_core::{lexer::Lexer, parser::Parser, resolver::Resolver, span::Span};
+
+/// Content-Type sebuah file berdasarkan ekstensinya,
+/// `application/octet-stream` jika tidak dikenali
+pub fn content_type(path: &Path) -> String {
+    mime_guess::from_path(path)
+        .first_or_octet_stream()
+        .to_string()
+}
+
+/// Membaca isi file untuk dikirim sebagai body
+///
+/// # Return
+/// pesan error yang menyebutkan path file jika file tidak bisa dibaca
+pub fn read_file(path: &Path) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|e| format!("cannot read file `{}`: {}", path.display(), e))
+}
+
+/// Body `multipart/form-data` yang sudah disusun menjadi bytes
+///
+/// body disimpan utuh di memory, sehingga request tetap bisa dikirim ulang
+/// saat retry atau digest auth.
+#[derive(Debug, Clone)]
+pub struct Multipart {
+    boundary: String,
+    body: Vec<u8>,
+}
+
+impl Default for Multipart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Multipart {
+    pub fn new() -> Self {
+        Self {
+            boundary: format!("nero-{:016x}", rand::random::<u64>()),
+            body: Vec::new(),
+        }
+    }
+
+    /// Menambahkan field teks
+    pub fn text(mut self, name: &str, value: &str) -> Self {
+        self.header(name, None, None);
+        self.body.extend_from_slice(value.as_bytes());
+        self.body.extend_from_slice(b"\r\n");
+        self
+    }
+
+    /// Menambahkan isi file, nama file dan Content-Type diambil dari `path`
+    pub fn file(mut self, name: &str, path: &Path) -> Result<Self, String> {
+        let data = read_file(path)?;
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        self.header(name, Some(&file_name), Some(&content_type(path)));
+        self.body.extend_from_slice(&data);
+        self.body.extend_from_slice(b"\r\n");
+        Ok(self)
+    }
+
+    /// Nilai header `Content-Type` beserta boundary-nya
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// Body lengkap, termasuk boundary penutup
+    pub fn into_bytes(mut self) -> Vec<u8> {
+        self.body
+            .extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        self.body
+    }
+
+    fn header(&mut self, name: &str, file_name: Option<&str>, content_type: Option<&str>) {
+        let mut header = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+            self.boundary,
+            escape(name)
+        );
+        if let Some(file_name) = file_name {
+            header.push_str(&format!("; filename=\"{}\"", escape(file_name)));
+        }
+        if let Some(content_type) = content_type {
+            header.push_str(&format!("\r\nContent-Type: {}", content_type));
+        }
+        header.push_str("\r\n\r\n");
+        self.body.extend_from_slice(header.as_bytes());
+    }
+}
+
+/// Escape nama field dan nama file di header `Content-Disposition`
+fn escape(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Mengganti `${var}` di dalam isi file dengan variabel dari `resolver`
+///
+/// # Return
+/// pesan error beserta lokasinya di dalam file, contoh:
+/// `payloads/user.json:3:12: Undefined variable: name`
+pub fn interpolate(path: &Path, data: Vec<u8>, resolver: &Resolver) -> Result<String, String> {
+    let text =
+        String::from_utf8(data).map_err(|_| format!("`{}` is not valid UTF-8", path.display()))?;
+    let error = |span: Span, e: &dyn std::fmt::Display| {
+        format!("{}:{}:{}: {}", path.display(), span.line, span.column, e)
+    };
+
+    let tokens = Lexer::tokenize_template(&text).map_err(|e| error(e.span(), &e))?;
+    let expr = Parser::new(tokens)
+        .parse_template()
+        .map_err(|e| error(e.span(), &e))?;
+    resolver
+        .resolve_expression(&expr)
+        .map(|value| value.to_string())
+        .map_err(|e| error(e.span(), &e))
+}
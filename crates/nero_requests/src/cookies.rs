@@ -0,0 +1,222 @@
+use std::{
+    fs,
+    io::BufReader,
+    path::Path,
+    sync::RwLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use cookie_store::{CookieDomain, CookieExpiration, CookieStore};
+use reqwest::{Url, header::HeaderValue};
+
+/// Format file cookie jar
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CookieFormat {
+    /// Format `cookies.txt` milik curl dan browser lama
+    Netscape,
+    /// Format JSON milik crate `cookie_store`
+    Json,
+}
+
+impl CookieFormat {
+    /// Menentukan format dari ekstensi file, atau dari isinya jika file sudah ada
+    pub fn detect(file: &Path, content: Option<&str>) -> Self {
+        let is_json = file.extension().is_some_and(|ext| ext == "json")
+            || content.is_some_and(|c| c.trim_start().starts_with(['[', '{']));
+        if is_json { Self::Json } else { Self::Netscape }
+    }
+}
+
+/// Cookie jar yang dipakai bersama oleh semua request dalam satu run
+///
+/// cookie dari `Set-Cookie` disimpan dan dikirim lagi ke request berikutnya
+/// yang cocok domain dan path-nya.
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    store: RwLock<CookieStore>,
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let mut store = self.store.write().unwrap_or_else(|e| e.into_inner());
+        for header in cookie_headers.filter_map(|h| h.to_str().ok()) {
+            // cookie yang tidak valid diabaikan, seperti browser
+            let _ = store.parse(header, url);
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let header = self.request_values(url, &[]);
+        (!header.is_empty())
+            .then(|| HeaderValue::from_str(&header).ok())
+            .flatten()
+    }
+}
+
+impl CookieJar {
+    /// Nilai header `Cookie` untuk `url`, ditambah cookie dari `extra` yang
+    /// menimpa cookie jar dengan nama yang sama
+    pub fn request_values(&self, url: &Url, extra: &[(String, String)]) -> String {
+        let store = self.store.read().unwrap_or_else(|e| e.into_inner());
+        store
+            .get_request_values(url)
+            .filter(|(name, _)| !extra.iter().any(|(n, _)| n == name))
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .chain(extra.iter().cloned())
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Membaca cookie jar dari file Netscape atau JSON
+    ///
+    /// file yang belum ada menghasilkan jar kosong, dan akan dibuat saat
+    /// [`CookieJar::save`] dipanggil.
+    pub fn load(file: &Path) -> Result<Self, String> {
+        if !file.exists() {
+            return Ok(Self::default());
+        }
+        let error = |e: &dyn std::fmt::Display| format!("{}: {}", file.display(), e);
+
+        let content = fs::read_to_string(file).map_err(|e| error(&e))?;
+        let store = match CookieFormat::detect(file, Some(&content)) {
+            CookieFormat::Json => {
+                cookie_store::serde::json::load_all(BufReader::new(content.as_bytes()))
+                    .map_err(|e| error(&e))?
+            }
+            CookieFormat::Netscape => Self::parse_netscape(&content).map_err(|e| error(&e))?,
+        };
+
+        Ok(Self {
+            store: RwLock::new(store),
+        })
+    }
+
+    /// Menyimpan semua cookie yang belum kedaluwarsa, termasuk cookie sesi
+    pub fn save(&self, file: &Path) -> Result<(), String> {
+        let error = |e: &dyn std::fmt::Display| format!("{}: {}", file.display(), e);
+        let content = fs::read_to_string(file).ok();
+        let store = self.store.read().unwrap_or_else(|e| e.into_inner());
+
+        let output = match CookieFormat::detect(file, content.as_deref()) {
+            CookieFormat::Json => {
+                let unexpired = store.iter_unexpired().cloned().map(Ok::<_, String>);
+                let unexpired = CookieStore::from_cookies(unexpired, false)?;
+                let mut output = Vec::new();
+                cookie_store::serde::json::save_incl_expired_and_nonpersistent(
+                    &unexpired,
+                    &mut output,
+                )
+                .map_err(|e| error(&e))?;
+                String::from_utf8_lossy(&output).to_string()
+            }
+            CookieFormat::Netscape => Self::to_netscape(&store),
+        };
+
+        fs::write(file, output).map_err(|e| error(&e))
+    }
+
+    /// Format Netscape, satu cookie per baris dipisah tab:
+    ///
+    /// ```text
+    /// domain  include_subdomains  path  secure  expires  name  value
+    /// ```
+    ///
+    /// `expires` bernilai `0` untuk cookie sesi, dan domain diawali
+    /// `#HttpOnly_` untuk cookie HttpOnly.
+    fn parse_netscape(content: &str) -> Result<CookieStore, String> {
+        let mut store = CookieStore::default();
+        let now = unix_now();
+
+        for (i, line) in content.lines().enumerate() {
+            let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+                Some(line) => (line, true),
+                None => (line, false),
+            };
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [domain, subdomains, path, secure, expires, name, value] = fields[..] else {
+                return Err(format!("line {}: expected 7 tab separated fields", i + 1));
+            };
+            let expires: i64 = expires
+                .parse()
+                .map_err(|_| format!("line {}: invalid expiry `{}`", i + 1, expires))?;
+            if expires != 0 && expires <= now {
+                continue;
+            }
+
+            let host = domain.trim_start_matches('.');
+            let secure = secure.eq_ignore_ascii_case("TRUE");
+            let mut cookie = format!("{}={}; Path={}", name, value, path);
+            if subdomains.eq_ignore_ascii_case("TRUE") {
+                cookie.push_str(&format!("; Domain={}", host));
+            }
+            if expires != 0 {
+                cookie.push_str(&format!("; Max-Age={}", expires - now));
+            }
+            if secure {
+                cookie.push_str("; Secure");
+            }
+            if http_only {
+                cookie.push_str("; HttpOnly");
+            }
+
+            let scheme = if secure { "https" } else { "http" };
+            let url = Url::parse(&format!("{}://{}{}", scheme, host, path))
+                .map_err(|e| format!("line {}: {}", i + 1, e))?;
+            store
+                .parse(&cookie, &url)
+                .map_err(|e| format!("line {}: {}", i + 1, e))?;
+        }
+
+        Ok(store)
+    }
+
+    fn to_netscape(store: &CookieStore) -> String {
+        let mut output = String::from("# Netscape HTTP Cookie File\n");
+
+        for cookie in store.iter_unexpired() {
+            let (domain, subdomains) = match &cookie.domain {
+                CookieDomain::HostOnly(domain) => (domain.clone(), "FALSE"),
+                CookieDomain::Suffix(domain) => (format!(".{}", domain), "TRUE"),
+                CookieDomain::NotPresent | CookieDomain::Empty => continue,
+            };
+            let expires = match &cookie.expires {
+                CookieExpiration::AtUtc(at) => at.unix_timestamp(),
+                CookieExpiration::SessionEnd => 0,
+            };
+
+            output.push_str(&format!(
+                "{}{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                if cookie.http_only().unwrap_or(false) {
+                    "#HttpOnly_"
+                } else {
+                    ""
+                },
+                domain,
+                subdomains,
+                String::from(&cookie.path),
+                if cookie.secure().unwrap_or(false) {
+                    "TRUE"
+                } else {
+                    "FALSE"
+                },
+                expires,
+                cookie.name(),
+                cookie.value()
+            ));
+        }
+
+        output
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
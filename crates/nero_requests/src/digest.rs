@@ -0,0 +1,185 @@
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+/// Algoritma hash yang didukung digest auth
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DigestAlgorithm {
+    Md5,
+    Md5Sess,
+    Sha256,
+    Sha256Sess,
+}
+
+impl DigestAlgorithm {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "MD5" => Some(Self::Md5),
+            "MD5-SESS" => Some(Self::Md5Sess),
+            "SHA-256" => Some(Self::Sha256),
+            "SHA-256-SESS" => Some(Self::Sha256Sess),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Md5Sess => "MD5-sess",
+            Self::Sha256 => "SHA-256",
+            Self::Sha256Sess => "SHA-256-sess",
+        }
+    }
+
+    fn hash(&self, data: &[u8]) -> String {
+        match self {
+            Self::Md5 | Self::Md5Sess => format!("{:x}", Md5::digest(data)),
+            Self::Sha256 | Self::Sha256Sess => format!("{:x}", Sha256::digest(data)),
+        }
+    }
+
+    fn is_session(&self) -> bool {
+        matches!(self, Self::Md5Sess | Self::Sha256Sess)
+    }
+}
+
+/// Challenge dari header `WWW-Authenticate: Digest ...` (RFC 7616)
+#[derive(Debug, PartialEq, Clone)]
+pub struct DigestChallenge {
+    pub realm: String,
+    pub nonce: String,
+    pub opaque: Option<String>,
+    /// `auth` atau `auth-int`, kosong untuk server lama (RFC 2069)
+    pub qop: Option<String>,
+    pub algorithm: DigestAlgorithm,
+}
+
+impl DigestChallenge {
+    /// Membaca nilai header `WWW-Authenticate`
+    ///
+    /// mengembalikan `None` jika bukan challenge digest atau algoritmanya
+    /// tidak didukung.
+    pub fn parse(header: &str) -> Option<Self> {
+        let (scheme, params) = header.trim().split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case("digest") {
+            return None;
+        }
+
+        let params = parse_params(params);
+        let get = |key: &str| {
+            params
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v.clone())
+        };
+
+        // utamakan `auth` jika server menawarkan keduanya
+        let qop = get("qop").and_then(|qop| {
+            let offered: Vec<&str> = qop.split(',').map(str::trim).collect();
+            ["auth", "auth-int"]
+                .into_iter()
+                .find(|q| offered.contains(q))
+                .map(String::from)
+        });
+
+        Some(Self {
+            realm: get("realm").unwrap_or_default(),
+            nonce: get("nonce")?,
+            opaque: get("opaque"),
+            qop,
+            algorithm: match get("algorithm") {
+                Some(name) => DigestAlgorithm::parse(&name)?,
+                None => DigestAlgorithm::Md5,
+            },
+        })
+    }
+
+    /// Nilai header `Authorization` untuk menjawab challenge ini
+    ///
+    /// `uri` adalah path beserta query request, `body` hanya dipakai
+    /// untuk qop `auth-int`.
+    pub fn authorization(
+        &self,
+        user: &str,
+        pass: &str,
+        method: &str,
+        uri: &str,
+        body: &[u8],
+        cnonce: &str,
+    ) -> String {
+        let alg = self.algorithm;
+        let nc = "00000001";
+
+        let mut ha1 = alg.hash(format!("{}:{}:{}", user, self.realm, pass).as_bytes());
+        if alg.is_session() {
+            ha1 = alg.hash(format!("{}:{}:{}", ha1, self.nonce, cnonce).as_bytes());
+        }
+
+        let ha2 = match self.qop.as_deref() {
+            Some("auth-int") => {
+                alg.hash(format!("{}:{}:{}", method, uri, alg.hash(body)).as_bytes())
+            }
+            _ => alg.hash(format!("{}:{}", method, uri).as_bytes()),
+        };
+
+        let response = match &self.qop {
+            Some(qop) => alg.hash(
+                format!("{}:{}:{}:{}:{}:{}", ha1, self.nonce, nc, cnonce, qop, ha2).as_bytes(),
+            ),
+            None => alg.hash(format!("{}:{}:{}", ha1, self.nonce, ha2).as_bytes()),
+        };
+
+        let mut header = format!(
+            r#"Digest username="{}", realm="{}", nonce="{}", uri="{}", algorithm={}, response="{}""#,
+            user,
+            self.realm,
+            self.nonce,
+            uri,
+            alg.as_str(),
+            response
+        );
+        if let Some(qop) = &self.qop {
+            header.push_str(&format!(r#", qop={}, nc={}, cnonce="{}""#, qop, nc, cnonce));
+        }
+        if let Some(opaque) = &self.opaque {
+            header.push_str(&format!(r#", opaque="{}""#, opaque));
+        }
+        header
+    }
+}
+
+/// Memecah `key=value, key="quoted, value"` menjadi pasangan key dan value
+fn parse_params(input: &str) -> Vec<(String, String)> {
+    let mut params = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    loop {
+        while matches!(chars.peek(), Some(c) if *c == ',' || c.is_whitespace()) {
+            chars.next();
+        }
+
+        let key: String = chars.by_ref().take_while(|c| *c != '=').collect();
+        if key.is_empty() {
+            break;
+        }
+
+        let mut value = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next()),
+                    '"' => break,
+                    c => value.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| *c != ',') {
+                value.push(c);
+            }
+        }
+
+        params.push((key.trim().to_string(), value.trim().to_string()));
+    }
+
+    params
+}
@@ -1,13 +1,38 @@
 use nero_core::{
-    ast::{Expr, Req},
-    resolver::{self, Resolver},
+    ast::{Body, Expr, Part, Req},
+    auth::{ApiKeyLocation, Auth, OAuth2},
+    options::RequestOptions,
+    resolver::{Resolver, ResolverError, Value},
 };
 use reqwest;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    body::{self, Multipart},
+    cookies::CookieJar,
+    digest::DigestChallenge,
+    oauth2::TokenCache,
+    response::{Attempt, Response},
+    sigv4,
+};
 
 #[derive(Debug)]
 pub enum ExecutorError {
     UnsupportedMethod,
     RequestFailed,
+    InvalidRequest(String),
+    Timeout,
+    TooManyRedirects,
+    /// Gagal mendapatkan kredensial, misalnya token OAuth2
+    Auth(String),
+    /// Semua percobaan gagal, berisi error terakhir dan jumlah percobaan
+    Exhausted(Box<ExecutorError>, usize),
+    Resolver(ResolverError),
 }
 
 impl std::error::Error for ExecutorError {}
@@ -16,64 +41,494 @@ impl std::fmt::Display for ExecutorError {
         match self {
             Self::UnsupportedMethod => write!(f, "Unsupported method"),
             Self::RequestFailed => write!(f, "Requests failed"),
+            Self::InvalidRequest(e) => write!(f, "Invalid request: {}", e),
+            Self::Timeout => write!(f, "Request timed out"),
+            Self::TooManyRedirects => write!(f, "Too many redirects"),
+            Self::Auth(e) => write!(f, "Authentication failed: {}", e),
+            Self::Exhausted(e, n) => write!(f, "{} after {} attempts", e, n),
+            Self::Resolver(e) => write!(f, "{}", e),
         }
     }
 }
 
-pub struct Executor<'a> {
-    pub resolver: &'a Resolver,
+impl From<ResolverError> for ExecutorError {
+    fn from(e: ResolverError) -> Self {
+        Self::Resolver(e)
+    }
+}
+
+/// Hook yang mengubah request yang sudah jadi tepat sebelum dikirim,
+/// misalnya untuk tanda tangan yang bergantung pada URL, header dan body
+pub type Signer = Arc<dyn Fn(&mut reqwest::Request) -> Result<(), String> + Send + Sync>;
+
+/// Clone dari executor memakai client, cookie dan token OAuth2 yang sama,
+/// sehingga bisa dipakai dengan [`Executor::base_dir`] yang berbeda
+#[derive(Clone)]
+pub struct Executor {
     pub client: reqwest::Client,
+    /// Batas waktu untuk setiap request
+    pub timeout: Option<Duration>,
+    /// Folder acuan untuk path relatif di dalam script, biasanya folder script
+    pub base_dir: Option<PathBuf>,
+    /// Token OAuth2 yang sudah diambil selama run ini
+    pub tokens: Arc<TokenCache>,
+    /// Cookie yang diterima selama run ini
+    pub cookies: Arc<CookieJar>,
+    /// Dijalankan berurutan pada setiap percobaan, sebelum tanda tangan `sigv4`
+    pub signers: Vec<Signer>,
 }
 
-impl<'a> Executor<'a> {
-    pub fn new(resolver: &'a Resolver) -> Self {
+impl Default for Executor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Executor {
+    pub fn new() -> Self {
+        Self::with_cookies(Arc::new(CookieJar::default()))
+    }
+
+    /// Executor yang menyimpan dan mengirim cookie lewat `cookies`
+    pub fn with_cookies(cookies: Arc<CookieJar>) -> Self {
         Self {
-            resolver,
-            client: reqwest::Client::new(),
+            client: Self::build_client(&cookies, reqwest::redirect::Policy::default())
+                .expect("failed to build HTTP client"),
+            timeout: None,
+            base_dir: None,
+            tokens: Arc::new(TokenCache::default()),
+            cookies,
+            signers: Vec::new(),
+        }
+    }
+
+    fn build_client(
+        cookies: &Arc<CookieJar>,
+        policy: reqwest::redirect::Policy,
+    ) -> reqwest::Result<reqwest::Client> {
+        reqwest::Client::builder()
+            .cookie_provider(cookies.clone())
+            .redirect(policy)
+            .build()
+    }
+
+    pub fn with_base_dir(mut self, base_dir: Option<PathBuf>) -> Self {
+        self.base_dir = base_dir;
+        self
+    }
+
+    /// Path dari script, relatif terhadap [`Executor::base_dir`]
+    pub fn path(&self, path: &str) -> PathBuf {
+        match &self.base_dir {
+            Some(dir) => dir.join(path),
+            None => PathBuf::from(path),
+        }
+    }
+
+    pub fn with_signer(mut self, signer: Signer) -> Self {
+        self.signers.push(signer);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn resolve_string(resolver: &Resolver, expr: &Expr) -> Result<String, ExecutorError> {
+        Ok(resolver.resolve_expression(expr)?.to_string())
+    }
+
+    fn resolve_json(resolver: &Resolver, expr: &Expr) -> Result<serde_json::Value, ExecutorError> {
+        Ok(resolver.resolve_expression(expr)?.to_json())
+    }
+
+    /// Mengubah section `OPTIONS` menjadi [`RequestOptions`]
+    fn resolve_options(
+        resolver: &Resolver,
+        options: &[(String, Expr)],
+    ) -> Result<RequestOptions, ExecutorError> {
+        let mut resolved = RequestOptions::default();
+        for (name, expr) in options {
+            let value = resolver.resolve_expression(expr)?;
+            resolved
+                .set(name, &value)
+                .map_err(ExecutorError::InvalidRequest)?;
+        }
+        Ok(resolved)
+    }
+
+    /// Client dengan aturan redirect sesuai `options`
+    ///
+    /// aturan redirect di reqwest berlaku per client, jadi client baru hanya
+    /// dibuat jika aturan default diubah.
+    fn client_for(&self, options: &RequestOptions) -> Result<reqwest::Client, ExecutorError> {
+        let policy = match (options.follow_redirects, options.max_redirects) {
+            (true, None) => return Ok(self.client.clone()),
+            (false, _) => reqwest::redirect::Policy::none(),
+            (true, Some(max)) => reqwest::redirect::Policy::limited(max),
+        };
+        Self::build_client(&self.cookies, policy)
+            .map_err(|e| ExecutorError::InvalidRequest(e.to_string()))
+    }
+
+    fn request_error(e: reqwest::Error) -> ExecutorError {
+        if e.is_timeout() {
+            ExecutorError::Timeout
+        } else if e.is_redirect() {
+            ExecutorError::TooManyRedirects
+        } else {
+            ExecutorError::RequestFailed
+        }
+    }
+
+    /// Mengirim request sekali, termasuk handshake auth yang dibutuhkan
+    async fn send(
+        &self,
+        client: &reqwest::Client,
+        mut request: reqwest::Request,
+        auth: Option<&Auth>,
+    ) -> Result<reqwest::Response, ExecutorError> {
+        for signer in &self.signers {
+            signer(&mut request).map_err(ExecutorError::InvalidRequest)?;
+        }
+
+        match auth {
+            Some(Auth::Digest { user, pass }) => {
+                Self::send_digest(client, request, user, pass).await
+            }
+            Some(Auth::OAuth2(config)) => self.send_oauth2(client, request, config).await,
+            // waktu tanda tangan harus baru di setiap percobaan
+            Some(Auth::SigV4(config)) => {
+                sigv4::sign(&mut request, config, chrono::Utc::now())
+                    .map_err(ExecutorError::InvalidRequest)?;
+                client.execute(request).await.map_err(Self::request_error)
+            }
+            _ => client.execute(request).await.map_err(Self::request_error),
         }
     }
 
-    fn resolve_string(&self, expr: &Expr) -> String {
-        match self.resolver.resolve_expression(expr).unwrap() {
-            resolver::Value::Number(n) => n.to_string(),
-            resolver::Value::String(s) => s,
+    fn try_clone(request: &reqwest::Request) -> Result<reqwest::Request, ExecutorError> {
+        request
+            .try_clone()
+            .ok_or_else(|| ExecutorError::InvalidRequest("request body cannot be resent".into()))
+    }
+
+    /// Request dikirim tanpa kredensial lebih dulu. Jika server membalas 401
+    /// dengan challenge digest, request dikirim ulang dengan header
+    /// `Authorization` yang menjawab challenge tersebut.
+    async fn send_digest(
+        client: &reqwest::Client,
+        request: reqwest::Request,
+        user: &str,
+        pass: &str,
+    ) -> Result<reqwest::Response, ExecutorError> {
+        let mut answer = Self::try_clone(&request)?;
+        let res = client.execute(request).await.map_err(Self::request_error)?;
+        if res.status() != reqwest::StatusCode::UNAUTHORIZED {
+            return Ok(res);
+        }
+
+        let Some(challenge) = res
+            .headers()
+            .get_all(reqwest::header::WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .find_map(DigestChallenge::parse)
+        else {
+            return Ok(res);
+        };
+
+        let url = answer.url();
+        let uri = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let body = answer.body().and_then(|b| b.as_bytes()).unwrap_or_default();
+        let cnonce = format!("{:016x}", rand::random::<u64>());
+        let header =
+            challenge.authorization(user, pass, answer.method().as_str(), &uri, body, &cnonce);
+        Self::set_header(&mut answer, reqwest::header::AUTHORIZATION, &header)?;
+
+        client.execute(answer).await.map_err(Self::request_error)
+    }
+
+    /// Token dari cache dikirim sebagai Bearer. Jika server membalas 401,
+    /// token diminta ulang sekali lalu request dikirim ulang.
+    async fn send_oauth2(
+        &self,
+        client: &reqwest::Client,
+        mut request: reqwest::Request,
+        config: &OAuth2,
+    ) -> Result<reqwest::Response, ExecutorError> {
+        let mut retry = Self::try_clone(&request)?;
+        let cache_file = config.cache.as_deref().map(|file| self.path(file));
+
+        let (token, fresh) = self
+            .tokens
+            .token(&self.client, config, cache_file.as_deref(), false)
+            .await?;
+        Self::set_bearer(&mut request, &token)?;
+
+        let res = client.execute(request).await.map_err(Self::request_error)?;
+        // token yang baru saja diminta tidak perlu diminta ulang
+        if res.status() != reqwest::StatusCode::UNAUTHORIZED || fresh {
+            return Ok(res);
         }
+
+        let (token, _) = self
+            .tokens
+            .token(&self.client, config, cache_file.as_deref(), true)
+            .await?;
+        Self::set_bearer(&mut retry, &token)?;
+        client.execute(retry).await.map_err(Self::request_error)
+    }
+
+    fn set_bearer(request: &mut reqwest::Request, token: &str) -> Result<(), ExecutorError> {
+        Self::set_header(
+            request,
+            reqwest::header::AUTHORIZATION,
+            &format!("Bearer {}", token),
+        )
+    }
+
+    fn set_header(
+        request: &mut reqwest::Request,
+        name: reqwest::header::HeaderName,
+        value: &str,
+    ) -> Result<(), ExecutorError> {
+        let value = reqwest::header::HeaderValue::from_str(value)
+            .map_err(|e| ExecutorError::InvalidRequest(e.to_string()))?;
+        request.headers_mut().insert(name, value);
+        Ok(())
     }
 
-    pub async fn execute(&self, req: &Req) -> Result<reqwest::Response, ExecutorError> {
-        let url = self.resolve_string(&req.url);
-        let mut request = match req.method.as_str() {
-            "GET" => self.client.get(&url),
-            "POST" => self.client.post(&url),
-            _ => return Err(ExecutorError::UnsupportedMethod),
+    /// Isi body beserta Content-Type bawaannya, sesuai jenis body
+    fn resolve_body(
+        &self,
+        body: &Body,
+        resolver: &Resolver,
+    ) -> Result<(Vec<u8>, String), ExecutorError> {
+        match body {
+            Body::Json(expr) => {
+                let json = serde_json::to_vec(&Self::resolve_json(resolver, expr)?)
+                    .map_err(|e| ExecutorError::InvalidRequest(e.to_string()))?;
+                Ok((json, "application/json".into()))
+            }
+            Body::Raw(expr) => Ok((
+                Self::resolve_string(resolver, expr)?.into_bytes(),
+                "text/plain; charset=utf-8".into(),
+            )),
+            Body::Form(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|(k, v)| Ok((k.clone(), Self::resolve_string(resolver, v)?)))
+                    .collect::<Result<Vec<_>, ExecutorError>>()?;
+                let form = serde_urlencoded::to_string(&fields)
+                    .map_err(|e| ExecutorError::InvalidRequest(e.to_string()))?;
+                Ok((
+                    form.into_bytes(),
+                    "application/x-www-form-urlencoded".into(),
+                ))
+            }
+            Body::Multipart(parts) => {
+                let mut multipart = Multipart::new();
+                for (name, part) in parts {
+                    multipart = match part {
+                        Part::Text(expr) => {
+                            multipart.text(name, &Self::resolve_string(resolver, expr)?)
+                        }
+                        Part::File(expr) => {
+                            let path = self.path(&Self::resolve_string(resolver, expr)?);
+                            multipart
+                                .file(name, &path)
+                                .map_err(ExecutorError::InvalidRequest)?
+                        }
+                    };
+                }
+                let content_type = multipart.content_type();
+                Ok((multipart.into_bytes(), content_type))
+            }
+            Body::File { path, interpolate } => {
+                let path = self.path(&Self::resolve_string(resolver, path)?);
+                let mut data = body::read_file(&path).map_err(ExecutorError::InvalidRequest)?;
+                if *interpolate {
+                    data = body::interpolate(&path, data, resolver)
+                        .map_err(ExecutorError::InvalidRequest)?
+                        .into_bytes();
+                }
+                Ok((data, body::content_type(&path)))
+            }
+        }
+    }
+
+    /// Menjalankan request dengan variabel dari `resolver`
+    ///
+    /// request diulang sesuai `OPTIONS { retries, retry_on, backoff, delay }`,
+    /// setiap percobaan dicatat di [`Response::attempts`].
+    pub async fn execute(&self, req: &Req, resolver: &Resolver) -> Result<Response, ExecutorError> {
+        let url = Self::resolve_string(resolver, &req.url)?;
+        let method = reqwest::Method::from_bytes(req.method.as_str().as_bytes())
+            .map_err(|_| ExecutorError::UnsupportedMethod)?;
+        let mut request = self.client.request(method.clone(), &url);
+
+        let body = match &req.body {
+            Some(body) => Some(self.resolve_body(body, resolver)?),
+            None => None,
         };
 
+        // `request` bisa dipakai di HEADERS dan QUERY, misalnya untuk
+        // menandatangani body: `hmac_sha256(secret, request.body)`
+        let scope = HashMap::from([(
+            "request".to_string(),
+            Value::Object(vec![
+                ("method".into(), Value::String(method.to_string())),
+                ("url".into(), Value::String(url.clone())),
+                (
+                    "body".into(),
+                    Value::String(
+                        body.as_ref()
+                            .map(|(data, _)| String::from_utf8_lossy(data).to_string())
+                            .unwrap_or_default(),
+                    ),
+                ),
+            ]),
+        )]);
+
         for (k, v) in &req.headers {
-            request = request.header(k, self.resolve_string(v));
+            request = request.header(k, resolver.resolve_scoped(v, &scope)?.to_string());
         }
 
         if !req.query.is_empty() {
             let q: Vec<(String, String)> = req
                 .query
                 .iter()
-                .map(|(k, v)| (k.clone(), self.resolve_string(v)))
-                .collect();
+                .map(|(k, v)| Ok((k.clone(), resolver.resolve_scoped(v, &scope)?.to_string())))
+                .collect::<Result<_, ExecutorError>>()?;
             request = request.query(&q)
         }
 
-        if let Some(body) = &req.body {
-            let mut map = serde_json::Map::new();
-            for (k, v) in body {
-                map.insert(k.clone(), serde_json::json!(self.resolve_string(v)));
+        // Content-Type diisi otomatis, kecuali sudah ditulis di section `HEADERS`
+        if let Some((data, content_type)) = body {
+            let has_content_type = req
+                .headers
+                .iter()
+                .any(|(k, _)| k.eq_ignore_ascii_case("content-type"));
+            if !has_content_type {
+                request = request.header(reqwest::header::CONTENT_TYPE, content_type);
             }
-            request = request.json(&map);
+            request = request.body(data);
         }
 
-        let res = request
-            .send()
-            .await
-            .map_err(|_| ExecutorError::RequestFailed)?;
+        let auth = match &req.auth {
+            Some((scheme, expr)) => Some(
+                Auth::new(scheme, &resolver.resolve_expression(expr)?)
+                    .map_err(ExecutorError::InvalidRequest)?,
+            ),
+            None => None,
+        };
+        match &auth {
+            Some(Auth::Basic { user, pass }) => request = request.basic_auth(user, Some(pass)),
+            Some(Auth::Bearer(token)) => request = request.bearer_auth(token),
+            Some(Auth::ApiKey {
+                location: ApiKeyLocation::Header,
+                name,
+                value,
+            }) => request = request.header(name, value),
+            Some(Auth::ApiKey {
+                location: ApiKeyLocation::Query,
+                name,
+                value,
+            }) => request = request.query(&[(name, value)]),
+            // dikirim saat request dijalankan, lihat [`Executor::send`]
+            Some(Auth::Digest { .. } | Auth::OAuth2(_) | Auth::SigV4(_)) | None => {}
+        }
+
+        let options = Self::resolve_options(resolver, &req.options)?;
+        if let Some(timeout) = options.timeout.or(self.timeout) {
+            request = request.timeout(timeout);
+        }
+
+        let mut request = request
+            .build()
+            .map_err(|e| ExecutorError::InvalidRequest(e.to_string()))?;
+        let url = request.url().to_string();
+
+        // cookie dari section `COOKIES` dikirim bersama cookie dari jar,
+        // dan menimpa cookie jar dengan nama yang sama
+        if !req.cookies.is_empty() {
+            let cookies = req
+                .cookies
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), Self::resolve_string(resolver, v)?)))
+                .collect::<Result<Vec<_>, ExecutorError>>()?;
+            let header = self.cookies.request_values(request.url(), &cookies);
+            Self::set_header(&mut request, reqwest::header::COOKIE, &header)?;
+        }
+        let client = self.client_for(&options)?;
+
+        let mut attempts: Vec<Attempt> = Vec::new();
+        let (res, start) = loop {
+            let attempt = attempts.len() as u32;
+            let last = attempt >= options.retries;
+            if attempt > 0 {
+                tokio::time::sleep(options.backoff.delay(options.delay, attempt)).await;
+            }
+
+            let request = request.try_clone().ok_or_else(|| {
+                ExecutorError::InvalidRequest("request body cannot be resent".into())
+            })?;
+
+            let start = Instant::now();
+            match self.send(&client, request, auth.as_ref()).await {
+                Ok(res) => {
+                    let status = res.status().as_u16();
+                    attempts.push(Attempt {
+                        status: Some(status),
+                        error: None,
+                        duration_ms: start.elapsed().as_millis(),
+                    });
+                    if last || !options.retry_on.contains(&status) {
+                        break (res, start);
+                    }
+                }
+                Err(error) => {
+                    attempts.push(Attempt {
+                        status: None,
+                        error: Some(error.to_string()),
+                        duration_ms: start.elapsed().as_millis(),
+                    });
+                    if last {
+                        return Err(match attempts.len() {
+                            1 => error,
+                            n => ExecutorError::Exhausted(Box::new(error), n),
+                        });
+                    }
+                }
+            }
+        };
+
+        let status = res.status().as_u16();
+        let content_length = res.content_length();
+        let headers = res
+            .headers()
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
+            .collect::<Vec<_>>();
+        let body = res.text().await.map_err(|_| ExecutorError::RequestFailed)?;
+        let duration_ms = start.elapsed().as_millis();
+        if let Some(attempt) = attempts.last_mut() {
+            attempt.duration_ms = duration_ms;
+        }
 
-        Ok(res)
+        Ok(Response {
+            url,
+            status,
+            size: content_length.unwrap_or(body.len() as u64),
+            headers,
+            body,
+            duration_ms,
+            attempts,
+        })
     }
 }
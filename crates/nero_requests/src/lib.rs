@@ -1 +1,7 @@
+pub mod body;
+pub mod cookies;
+pub mod digest;
 pub mod executor;
+pub mod oauth2;
+pub mod response;
+pub mod sigv4;
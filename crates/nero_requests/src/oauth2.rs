@@ -0,0 +1,193 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use nero_core::auth::{OAuth2, OAuth2Grant};
+use tokio::sync::Mutex;
+
+use crate::executor::ExecutorError;
+
+/// Token dianggap kedaluwarsa sedikit lebih awal, supaya tidak habis di
+/// tengah jalan
+const EXPIRY_LEEWAY_SECS: u64 = 10;
+
+/// Access token dari token endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub access_token: String,
+    /// Waktu kedaluwarsa dalam detik sejak unix epoch, kosong jika server
+    /// tidak mengirim `expires_in`
+    pub expires_at: Option<u64>,
+}
+
+impl Token {
+    pub fn is_valid(&self) -> bool {
+        self.expires_at
+            .is_none_or(|expires_at| now() + EXPIRY_LEEWAY_SECS < expires_at)
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "access_token": self.access_token,
+            "expires_at": self.expires_at,
+        })
+    }
+
+    fn from_json(value: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            access_token: value.get("access_token")?.as_str()?.to_string(),
+            expires_at: value.get("expires_at").and_then(|v| v.as_u64()),
+        })
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Cache token OAuth2 yang dipakai bersama oleh semua request dalam satu run
+///
+/// Token disimpan di memory, dan juga di file jika `cache` diisi di section
+/// `AUTH`. Lock ditahan selama token diambil, sehingga request yang berjalan
+/// paralel tidak meminta token berkali-kali.
+///
+/// Token tanpa `expires_in` hanya disimpan di memory, karena tidak bisa
+/// diketahui kapan token itu kedaluwarsa di run berikutnya.
+#[derive(Debug, Default)]
+pub struct TokenCache {
+    tokens: Mutex<HashMap<String, Token>>,
+}
+
+impl TokenCache {
+    /// Access token untuk `config`
+    ///
+    /// token baru diminta jika belum ada, sudah kedaluwarsa, atau `refresh`
+    /// bernilai `true`.
+    ///
+    /// # Return
+    /// access token, dan `true` jika token baru saja diminta
+    pub async fn token(
+        &self,
+        client: &reqwest::Client,
+        config: &OAuth2,
+        cache_file: Option<&Path>,
+        refresh: bool,
+    ) -> Result<(String, bool), ExecutorError> {
+        let key = Self::key(config);
+        let mut tokens = self.tokens.lock().await;
+
+        if !refresh {
+            let cached = tokens
+                .get(&key)
+                .cloned()
+                .or_else(|| cache_file.and_then(|file| Self::read_file(file, &key)));
+            if let Some(token) = cached.filter(Token::is_valid) {
+                tokens.insert(key, token.clone());
+                return Ok((token.access_token, false));
+            }
+        }
+
+        let token = Self::request(client, config).await?;
+        if let Some(file) = cache_file.filter(|_| token.expires_at.is_some()) {
+            Self::write_file(file, &key, &token)?;
+        }
+        tokens.insert(key, token.clone());
+        Ok((token.access_token, true))
+    }
+
+    /// Kunci cache, tanpa menyertakan secret
+    fn key(config: &OAuth2) -> String {
+        [
+            config.grant.as_str(),
+            &config.token_url,
+            &config.client_id,
+            config.username.as_deref().unwrap_or(""),
+            config.scope.as_deref().unwrap_or(""),
+        ]
+        .join(" ")
+    }
+
+    async fn request(client: &reqwest::Client, config: &OAuth2) -> Result<Token, ExecutorError> {
+        let mut form = vec![("grant_type", config.grant.as_str())];
+        if let Some(scope) = &config.scope {
+            form.push(("scope", scope));
+        }
+        if let (OAuth2Grant::Password, Some(username), Some(password)) =
+            (config.grant, &config.username, &config.password)
+        {
+            form.push(("username", username));
+            form.push(("password", password));
+        }
+
+        let mut request = client.post(&config.token_url);
+        if config.client_auth_body {
+            form.push(("client_id", &config.client_id));
+            if let Some(secret) = &config.client_secret {
+                form.push(("client_secret", secret));
+            }
+        } else {
+            request = request.basic_auth(&config.client_id, config.client_secret.as_ref());
+        }
+
+        let res = request
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| ExecutorError::Auth(format!("token request failed: {}", e)))?;
+        let status = res.status();
+        let body = res.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(ExecutorError::Auth(format!(
+                "token endpoint returned {}: {}",
+                status, body
+            )));
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&body)
+            .map_err(|_| ExecutorError::Auth(format!("invalid token response: {}", body)))?;
+        let access_token = json
+            .get("access_token")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ExecutorError::Auth("token response has no access_token".into()))?;
+        // sebagian server mengirim `expires_in` sebagai string
+        let expires_in = json.get("expires_in").and_then(|v| match v {
+            serde_json::Value::String(s) => s.parse::<u64>().ok(),
+            v => v.as_u64(),
+        });
+
+        Ok(Token {
+            access_token: access_token.to_string(),
+            expires_at: expires_in.map(|secs| now() + secs),
+        })
+    }
+
+    fn read_file(file: &Path, key: &str) -> Option<Token> {
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(file).ok()?).ok()?;
+        Token::from_json(json.get(key)?).filter(|token| token.expires_at.is_some())
+    }
+
+    /// Menyimpan token ke file cache tanpa menghapus token lain di dalamnya
+    fn write_file(file: &Path, key: &str, token: &Token) -> Result<(), ExecutorError> {
+        let mut json = fs::read_to_string(file)
+            .ok()
+            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+            .filter(|v| v.is_object())
+            .unwrap_or_else(|| serde_json::json!({}));
+        json[key] = token.to_json();
+
+        let content = serde_json::to_string_pretty(&json).unwrap_or_default();
+        fs::write(file, content).map_err(|e| {
+            ExecutorError::Auth(format!(
+                "cannot write token cache {}: {}",
+                file.display(),
+                e
+            ))
+        })
+    }
+}
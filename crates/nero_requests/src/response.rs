@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use nero_core::resolver::Value;
+
+/// Response dari sebuah request yang sudah dijalankan
+#[derive(Debug, Clone)]
+pub struct Response {
+    /// URL yang dikirim, setelah semua variabel dan query di-resolve
+    pub url: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    pub size: u64,
+    pub duration_ms: u128,
+    /// Semua percobaan yang dilakukan, termasuk yang terakhir.
+    /// Berisi lebih dari satu item jika request diulang, lihat `OPTIONS { retries }`
+    pub attempts: Vec<Attempt>,
+}
+
+/// Satu kali percobaan mengirim request
+#[derive(Debug, Clone)]
+pub struct Attempt {
+    /// Status code, kosong jika request gagal terkirim
+    pub status: Option<u16>,
+    /// Alasan request gagal terkirim
+    pub error: Option<String>,
+    pub duration_ms: u128,
+}
+
+impl Response {
+    /// Body response dalam bentuk [`Value`]
+    ///
+    /// jika body berupa JSON yang valid, maka akan di-parse,
+    /// selain itu dikembalikan sebagai string.
+    pub fn body_value(&self) -> Value {
+        serde_json::from_str::<serde_json::Value>(&self.body)
+            .map(Value::from)
+            .unwrap_or_else(|_| Value::String(self.body.clone()))
+    }
+
+    /// Variabel yang bisa dipakai di block `CAPTURE`
+    ///
+    /// - `status` status code response
+    /// - `header` object berisi header response
+    /// - `body` body response, lihat [`Response::body_value`]
+    /// - `duration` lama request dalam milidetik
+    pub fn scope(&self) -> HashMap<String, Value> {
+        let headers = self
+            .headers
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect();
+
+        HashMap::from([
+            ("status".to_string(), Value::Number(self.status as i64)),
+            ("header".to_string(), Value::Object(headers)),
+            ("body".to_string(), self.body_value()),
+            (
+                "duration".to_string(),
+                Value::Number(self.duration_ms as i64),
+            ),
+        ])
+    }
+}
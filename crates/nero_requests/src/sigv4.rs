@@ -0,0 +1,214 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use nero_core::auth::SigV4;
+use reqwest::header::{HeaderName, HeaderValue};
+use sha2::{Digest, Sha256};
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// Menandatangani `request` dengan AWS Signature Version 4
+///
+/// header `X-Amz-Date` (dan `X-Amz-Security-Token` jika ada) ditambahkan
+/// lalu ikut ditandatangani bersama header lain yang sudah ada di request.
+/// Query diurutkan dan di-encode ulang supaya sama persis dengan yang
+/// ditandatangani.
+pub fn sign(
+    request: &mut reqwest::Request,
+    config: &SigV4,
+    time: DateTime<Utc>,
+) -> Result<(), String> {
+    let amz_date = time.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = time.format("%Y%m%d").to_string();
+    let payload_hash = hex(&Sha256::digest(
+        request
+            .body()
+            .and_then(|b| b.as_bytes())
+            .unwrap_or_default(),
+    ));
+
+    let headers = request.headers_mut();
+    headers.remove(reqwest::header::AUTHORIZATION);
+    set_header(headers, "x-amz-date", &amz_date)?;
+    if let Some(token) = &config.session_token {
+        set_header(headers, "x-amz-security-token", token)?;
+    }
+    // S3 mewajibkan hash body dikirim sebagai header
+    if config.service == "s3" {
+        set_header(headers, "x-amz-content-sha256", &payload_hash)?;
+    }
+
+    let query = canonical_query(request.url());
+    request
+        .url_mut()
+        .set_query((!query.is_empty()).then_some(query.as_str()));
+
+    let (signed_headers, canonical_headers) = canonical_headers(request);
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method().as_str(),
+        canonical_path(request.url().path(), &config.service),
+        query,
+        canonical_headers,
+        signed_headers,
+        payload_hash
+    );
+
+    let scope = format!("{}/{}/{}/aws4_request", date, config.region, config.service);
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        ALGORITHM,
+        amz_date,
+        scope,
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = [
+        date.as_str(),
+        config.region.as_str(),
+        config.service.as_str(),
+        "aws4_request",
+    ]
+    .iter()
+    .fold(
+        format!("AWS4{}", config.secret_key).into_bytes(),
+        |key, part| hmac_sha256(&key, part.as_bytes()),
+    );
+    let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    let authorization = format!(
+        "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+        ALGORITHM, config.access_key, scope, signed_headers, signature
+    );
+    set_header(request.headers_mut(), "authorization", &authorization)
+}
+
+fn set_header(
+    headers: &mut reqwest::header::HeaderMap,
+    name: &'static str,
+    value: &str,
+) -> Result<(), String> {
+    let value = HeaderValue::from_str(value).map_err(|e| e.to_string())?;
+    headers.insert(HeaderName::from_static(name), value);
+    Ok(())
+}
+
+/// Path yang sudah di-encode di URL di-encode sekali lagi, kecuali untuk S3
+/// yang memakai path apa adanya
+fn canonical_path(path: &str, service: &str) -> String {
+    let path = if path.is_empty() { "/" } else { path };
+    if service == "s3" {
+        uri_encode(&percent_decode(path), false)
+    } else {
+        uri_encode(path.as_bytes(), false)
+    }
+}
+
+/// Pasangan query di-encode ulang lalu diurutkan berdasarkan nama dan nilai
+fn canonical_query(url: &reqwest::Url) -> String {
+    let mut pairs = url
+        .query_pairs()
+        .map(|(k, v)| {
+            (
+                uri_encode(k.as_bytes(), true),
+                uri_encode(v.as_bytes(), true),
+            )
+        })
+        .collect::<Vec<_>>();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Daftar nama header yang ditandatangani dan isi header kanonikal
+///
+/// `Host` tidak ada di header request karena baru ditambahkan saat
+/// request dikirim, jadi diambil dari URL.
+fn canonical_headers(request: &reqwest::Request) -> (String, String) {
+    let mut headers: Vec<(String, Vec<String>)> = Vec::new();
+    if !request.headers().contains_key(reqwest::header::HOST) {
+        let url = request.url();
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        headers.push(("host".into(), vec![host]));
+    }
+
+    for (name, value) in request.headers() {
+        if name == reqwest::header::AUTHORIZATION {
+            continue;
+        }
+        let value = String::from_utf8_lossy(value.as_bytes())
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        match headers.iter_mut().find(|(k, _)| k == name.as_str()) {
+            Some((_, values)) => values.push(value),
+            None => headers.push((name.as_str().to_string(), vec![value])),
+        }
+    }
+    headers.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let signed = headers
+        .iter()
+        .map(|(k, _)| k.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical = headers
+        .iter()
+        .map(|(k, v)| format!("{}:{}\n", k, v.join(",")))
+        .collect::<String>();
+    (signed, canonical)
+}
+
+/// Percent-encoding versi AWS, hanya karakter unreserved (RFC 3986) yang
+/// tidak di-encode
+fn uri_encode(data: &[u8], encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(data.len());
+    for &byte in data {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn percent_decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -1,3 +1,7 @@
+// access token yang masih berlaku, dipakai untuk mencoba `AUTH { oauth2 }`
+const tokens = new Set<string>();
+let issued = 0;
+
 const server = Bun.serve({
   routes: {
     "/": Response.json({ msg: "Ok" }),
@@ -6,6 +10,31 @@ const server = Bun.serve({
         return new Response("Get user");
       },
     },
+    "/oauth/token": {
+      POST: async (req) => {
+        const form = await req.formData();
+        const grant = form.get("grant_type");
+        if (grant !== "client_credentials" && grant !== "password") {
+          return Response.json({ error: "unsupported_grant_type" }, { status: 400 });
+        }
+        const token = `token-${++issued}`;
+        tokens.add(token);
+        return Response.json({ access_token: token, token_type: "Bearer", expires_in: 3600 });
+      },
+    },
+    // mencabut semua token, untuk mencoba refresh setelah 401
+    "/oauth/revoke": {
+      POST: () => {
+        tokens.clear();
+        return new Response(null, { status: 204 });
+      },
+    },
+    "/protected": (req) => {
+      const token = (req.headers.get("authorization") ?? "").replace(/^Bearer /, "");
+      return tokens.has(token)
+        ? Response.json({ msg: "Ok" })
+        : new Response("Unauthorized", { status: 401 });
+    },
   },
 
   fetch(req) {
@@ -1,3 +1,12 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
 use nero_core::{
     ast::Stmt,
     lexer::Lexer,
@@ -11,13 +20,186 @@ pub struct TestUtils;
 impl TestUtils {
     pub fn parse_ok(input: &str) -> Vec<Stmt> {
         let tokens = Lexer::tokenize(input).unwrap();
-        let mut parser = Parser { tokens, pos: 0 };
+        let mut parser = Parser::new(tokens);
         parser.parse().unwrap()
     }
 
     pub fn parse_err(input: &str) -> ParserError {
         let tokens = Lexer::tokenize(input).unwrap();
-        let mut parser = Parser { tokens, pos: 0 };
+        let mut parser = Parser::new(tokens);
         parser.parse().unwrap_err()
     }
+
+    /// Folder kosong di temp dir, unik untuk setiap test
+    pub fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nero-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Menulis `content` ke `dir/file`, lalu mengembalikan path-nya
+    pub fn write(dir: &Path, file: &str, content: &str) -> String {
+        let path = dir.join(file);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }
+}
+
+/// Request yang diterima oleh [`Stub`]
+#[allow(unused)]
+#[derive(Debug, Clone)]
+pub struct StubRequest {
+    pub method: String,
+    /// Path beserta query string
+    pub path: String,
+    /// Nama header dalam huruf kecil
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+#[allow(unused)]
+impl StubRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Response yang dikirim oleh [`Stub`]
+#[allow(unused)]
+pub struct StubResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    /// Jeda sebelum response dikirim
+    pub delay: Duration,
+}
+
+#[allow(unused)]
+impl StubResponse {
+    pub fn new(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.to_string(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self::new(status, &body.to_string()).header("content-type", "application/json")
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn delay(mut self, millis: u64) -> Self {
+        self.delay = Duration::from_millis(millis);
+        self
+    }
+}
+
+type Handler = dyn Fn(&StubRequest) -> StubResponse + Send + Sync;
+
+/// HTTP server lokal untuk test executor, setiap koneksi dilayani di
+/// thread sendiri sehingga request paralel tidak saling menunggu
+#[allow(unused)]
+pub struct Stub {
+    /// Contoh: `http://127.0.0.1:40123`
+    pub url: String,
+    /// Semua request yang diterima, sesuai urutan datangnya
+    pub requests: Arc<Mutex<Vec<StubRequest>>>,
+}
+
+#[allow(unused)]
+impl Stub {
+    pub fn start(handler: impl Fn(&StubRequest) -> StubResponse + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let received = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (handler, received) = (handler.clone(), received.clone());
+                thread::spawn(move || Self::serve(stream, &*handler, &received));
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// Request yang diterima dengan path diawali `prefix`
+    pub fn received(&self, prefix: &str) -> Vec<StubRequest> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.path.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+
+    fn serve(stream: TcpStream, handler: &Handler, received: &Mutex<Vec<StubRequest>>) {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let mut parts = line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let path = parts.next().unwrap_or_default().to_string();
+
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+                break;
+            }
+            if let Some((k, v)) = line.split_once(':') {
+                headers.push((k.trim().to_ascii_lowercase(), v.trim().to_string()));
+            }
+        }
+
+        let length = headers
+            .iter()
+            .find(|(k, _)| k == "content-length")
+            .and_then(|(_, v)| v.parse().ok())
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        let _ = reader.read_exact(&mut body);
+
+        let request = StubRequest {
+            method,
+            path,
+            headers,
+            body: String::from_utf8_lossy(&body).to_string(),
+        };
+        received.lock().unwrap().push(request.clone());
+
+        let response = handler(&request);
+        thread::sleep(response.delay);
+        let mut head = format!(
+            "HTTP/1.1 {} Stub\r\ncontent-length: {}\r\nconnection: close\r\n",
+            response.status,
+            response.body.len()
+        );
+        for (k, v) in &response.headers {
+            head.push_str(&format!("{}: {}\r\n", k, v));
+        }
+        head.push_str("\r\n");
+
+        let mut stream = reader.into_inner();
+        let _ = stream.write_all(head.as_bytes());
+        let _ = stream.write_all(response.body.as_bytes());
+    }
 }